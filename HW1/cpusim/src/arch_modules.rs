@@ -1,20 +1,81 @@
-use serde::Serialize;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
 
-const ALLOWED_OP_CODES: [&str; 5] = ["add", "sub", "mulu", "divu", "remu"];
-const IMMEDIATE_OP_CODES: [&str; 1] = ["addi"];
+const ALLOWED_OP_CODES: [&str; 15] = [
+    "add", "sub", "mulu", "divu", "remu", "div", "rem", "and", "or", "xor", "sll", "srl", "sra",
+    "slt", "sltu",
+];
+const IMMEDIATE_OP_CODES: [&str; 10] = [
+    "addi", "subi", "andi", "ori", "xori", "slli", "srli", "srai", "slti", "sltiu",
+];
+/// Immediate form of "sltu", named as in RISC-V with the "i" before the "u".
+const SLTU_IMMEDIATE_OP_CODE: &str = "sltiu";
+/// Loads ("ld rd, imm(rs1)") and stores ("st rs2, imm(rs1)") of 64-bit words.
+const MEMORY_OP_CODES: [&str; 2] = ["ld", "st"];
+/// Conditional branches ("beq rs1, rs2, target") to an absolute instruction index.
+const BRANCH_OP_CODES: [&str; 2] = ["beq", "bne"];
+/// Unconditional jump ("jal rd, target") writing the return address to rd.
+const JUMP_OP_CODE: &str = "jal";
+/// Instruction without operands doing nothing, retiring as soon as it is dispatched.
+const NOP_OP_CODE: &str = "nop";
+/// Instructions without operands ending the program once they retire.
+const HALT_OP_CODES: [&str; 2] = ["halt", "ecall"];
+/// Load upper immediate ("lui rd, imm") writing the immediate shifted left by 12 bits to rd.
+const LUI_OP_CODE: &str = "lui";
+/// Number of bits `lui` shifts its immediate by, leaving room for an `addi` of the low bits.
+const LUI_SHIFT: u32 = 12;
+/// Pseudo-instruction "mv rd, rs" copying rs to rd, expanded to "addi rd, rs, 0".
+const MOVE_OP_CODE: &str = "mv";
+/// Pseudo-instruction "li rd, imm" loading imm to rd, expanded to "addi rd, x0, imm". It relies on
+/// x0 reading zero, which only the hardwired zero register guarantees, so it is rejected at fetch
+/// otherwise.
+const LOAD_IMMEDIATE_OP_CODE: &str = "li";
+const LOGICAL_REGISTER_COUNT: u8 = 32;
+/// RISC-V ABI names of the logical registers, indexed by register number.
+const ABI_REGISTER_NAMES: [&str; LOGICAL_REGISTER_COUNT as usize] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+/// Alternative ABI name of s0.
+const FRAME_POINTER_NAME: &str = "fp";
 
-#[derive(Clone, Serialize)]
+/// Reason an instruction raised an exception, serialized as its name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExceptionCause {
+    /// `divu`, `remu`, `div` or `rem` by zero.
+    DivideByZero,
+    /// Unsigned overflow when trapping on overflow, or signed `i64::MIN / -1`.
+    Overflow,
+    /// Unsigned `sub` result below zero when trapping on overflow.
+    Underflow,
+    /// Load or store to a misaligned or out of bounds address.
+    InvalidAddress,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ActiveListEntry {
     #[serde(rename = "Done")]
     pub is_done: bool,
     #[serde(rename = "Exception")]
     pub is_exception: bool,
+    #[serde(rename = "ExceptionCause")]
+    pub exception_cause: Option<ExceptionCause>,
     #[serde(rename = "LogicalDestination")]
     pub logical_destination: u8,
     #[serde(rename = "OldDestination")]
     pub old_destination: u8,
     #[serde(rename = "PC")]
     pub pc: u64,
+    #[serde(skip)] // a physical register was allocated for the destination
+    pub has_destination: bool,
+    #[serde(skip)] // the program ends when the instruction retires
+    pub is_halt: bool,
+    #[serde(skip)] // counted in the instruction mix when the instruction retires
+    pub op_code: String,
+    #[serde(skip)] // cycles spent in the active list, for the instruction watchdog
+    pub age: usize,
 }
 
 impl ActiveListEntry {
@@ -24,18 +85,24 @@ impl ActiveListEntry {
         logical_destination: u8,
         old_destination: u8,
         pc: u64,
+        has_destination: bool,
     ) -> ActiveListEntry {
         ActiveListEntry {
             is_done: done,
             is_exception: exception,
+            exception_cause: None,
             logical_destination,
             old_destination,
             pc,
+            has_destination,
+            is_halt: false,
+            op_code: String::new(),
+            age: 0,
         }
     }
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct IntegerQueueEntry {
     #[serde(rename = "DestRegister")]
     pub dest_register: u8,
@@ -55,9 +122,22 @@ pub struct IntegerQueueEntry {
     pub op_code: String,
     #[serde(rename = "PC")]
     pub pc: u64,
+    #[serde(skip)] // operand B holds a sign-extended immediate
+    pub is_immediate: bool,
+    #[serde(skip)] // address offset of stores or target of branches
+    pub offset: u64,
+    #[serde(skip)] // the destination register receives a value
+    pub writes_register: bool,
+    #[serde(skip)] // physical registers the operands are read from, for self-checks
+    pub source_registers: Vec<u8>,
+    #[serde(skip)] // cycle the instruction entered the queue, for wait statistics
+    pub dispatch_cycle: u64,
+    #[serde(skip)] // slot of the fixed-size queue the entry occupies
+    pub slot: usize,
 }
 
 impl IntegerQueueEntry {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         dest_register: u8,
         op_a_is_ready: bool,
@@ -68,6 +148,9 @@ impl IntegerQueueEntry {
         op_b_value: u64,
         op_code: String,
         pc: u64,
+        is_immediate: bool,
+        offset: u64,
+        writes_register: bool,
     ) -> IntegerQueueEntry {
         IntegerQueueEntry {
             dest_register,
@@ -79,6 +162,12 @@ impl IntegerQueueEntry {
             op_b_value,
             op_code,
             pc,
+            is_immediate,
+            offset,
+            writes_register,
+            source_registers: Vec::new(),
+            dispatch_cycle: 0,
+            slot: 0,
         }
     }
 
@@ -89,118 +178,350 @@ impl IntegerQueueEntry {
 
 #[derive(Clone, Serialize)]
 pub struct ALUEntry {
+    #[serde(rename = "DestRegister")]
     dest_register: u8,
+    #[serde(rename = "OpAValue")]
     op_a_value: u64,
+    #[serde(rename = "OpBValue")]
     op_b_value: u64,
+    #[serde(rename = "OpCode")]
     op_code: String,
+    #[serde(rename = "PC")]
     pc: u64,
+    #[serde(rename = "IsImmediate")]
+    is_immediate: bool,
+    #[serde(rename = "Offset")]
+    offset: u64,
+    #[serde(rename = "WritesRegister")]
+    writes_register: bool,
+    #[serde(rename = "RemainingCycles")]
+    remaining_cycles: usize,
+    /// Partner of a fused `divu`/`remu` pair, whose result comes out of the same division.
+    #[serde(rename = "FusedPartner")]
+    fused_partner: Option<Box<ALUEntry>>,
 }
 
 impl ALUEntry {
-    pub fn new(
-        dest_register: u8,
-        op_a_value: u64,
-        op_b_value: u64,
-        op_code: String,
-        pc: u64,
-    ) -> ALUEntry {
+    pub fn new(entry: IntegerQueueEntry, remaining_cycles: usize) -> ALUEntry {
         ALUEntry {
-            dest_register,
-            op_a_value,
-            op_b_value,
-            op_code,
-            pc,
+            dest_register: entry.dest_register,
+            op_a_value: entry.op_a_value,
+            op_b_value: entry.op_b_value,
+            op_code: entry.op_code,
+            pc: entry.pc,
+            is_immediate: entry.is_immediate,
+            offset: entry.offset,
+            writes_register: entry.writes_register,
+            remaining_cycles,
+            fused_partner: None,
         }
     }
+
+    /// Drops the instructions of the entry younger than `pc`. When only the younger half of a
+    /// fused pair is dropped, the older half carries on alone with the remaining latency.
+    fn squash_younger_than(mut self, pc: u64) -> Option<ALUEntry> {
+        let partner = self.fused_partner.take().filter(|partner| partner.pc <= pc);
+        if self.pc <= pc {
+            self.fused_partner = partner;
+            return Some(self);
+        }
+        partner.map(|partner| ALUEntry {
+            remaining_cycles: self.remaining_cycles,
+            ..*partner
+        })
+    }
+}
+
+/// Result of the partner of a fused `divu`/`remu` pair, forwarded by the same ALU in the same
+/// cycle as the result of the instruction it was issued with, from the same operands.
+#[derive(Clone, Serialize)]
+pub struct FusedResult {
+    #[serde(rename = "Reg")]
+    pub reg: u8,
+    #[serde(rename = "Value")]
+    pub value: u64,
+    #[serde(rename = "PC")]
+    pub pc: u64,
+    #[serde(rename = "Exception")]
+    pub exception: Option<ExceptionCause>,
+    #[serde(skip_serializing)] // provenance of the result, for debugging
+    pub op_code: String,
+}
+
+/// Memory access of a load or store, performed in order when the instruction retires.
+#[derive(Clone, Copy, Serialize)]
+pub enum MemoryAccess {
+    Load {
+        #[serde(rename = "Address")]
+        address: u64,
+    },
+    Store {
+        #[serde(rename = "Address")]
+        address: u64,
+        #[serde(rename = "Value")]
+        value: u64,
+    },
 }
 
+/// Result of a completed instruction waiting for it to retire, with the operation and operand
+/// values it was produced from.
 #[derive(Clone, Serialize)]
 pub struct CommitBufferEntry {
     pub dest_register: u8,
     pub value: u64,
     pub pc: u64,
+    pub memory_access: Option<MemoryAccess>,
+    pub redirect_pc: Option<u64>,
+    pub op_code: String,
+    pub op_a_value: u64,
+    pub op_b_value: u64,
 }
 
 impl CommitBufferEntry {
-    pub fn new(dest_register: u8, value: u64, pc: u64) -> CommitBufferEntry {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        dest_register: u8,
+        value: u64,
+        pc: u64,
+        memory_access: Option<MemoryAccess>,
+        redirect_pc: Option<u64>,
+        op_code: String,
+        op_a_value: u64,
+        op_b_value: u64,
+    ) -> CommitBufferEntry {
         CommitBufferEntry {
             dest_register,
             value,
             pc,
+            memory_access,
+            redirect_pc,
+            op_code,
+            op_a_value,
+            op_b_value,
+        }
+    }
+}
+
+/// Explains the result with the operation it was produced from.
+///
+/// ex: "PC 2: mulu 6, 7 -> p33 = 42"
+impl fmt::Display for CommitBufferEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PC {}: {} {}, {} -> p{} = {}",
+            self.pc, self.op_code, self.op_a_value, self.op_b_value, self.dest_register, self.value
+        )
+    }
+}
+
+/// Execute stages of an ALU, from the stage instructions are issued to up to the stage their
+/// result is forwarded from. Logged as "Stage1" to "StageN".
+#[derive(Clone)]
+struct ALUStages(Vec<Option<ALUEntry>>);
+
+impl Serialize for ALUStages {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (index, stage) in self.0.iter().enumerate() {
+            map.serialize_entry(&format!("Stage{}", index + 1), stage)?;
         }
+        map.end()
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Serialize)]
 pub struct ALU {
-    stage1: Option<ALUEntry>,
-    stage2: Option<ALUEntry>,
+    #[serde(flatten)]
+    stages: ALUStages,
+    #[serde(rename = "IsForwarding")]
     pub is_forwarding: bool,
+    #[serde(rename = "ForwardingReg")]
     pub forwarding_reg: u8,
+    #[serde(rename = "ForwardingValue")]
     pub forwarding_value: u64,
+    #[serde(rename = "ForwardingPC")]
     pub forwarding_pc: u64,
-    pub forwarding_exception: bool,
+    #[serde(rename = "ForwardingException")]
+    pub forwarding_exception: Option<ExceptionCause>,
+    /// Set when the forwarded value is the one of the destination register. Stores and branches
+    /// write no register, and loads only know their value once memory is read at commit.
+    #[serde(rename = "ForwardingWritesRegister")]
+    pub forwarding_writes_register: bool,
+    /// Set for loads and stores, whose result is a memory access rather than a register value.
+    #[serde(rename = "ForwardingMemoryAccess")]
+    pub forwarding_memory_access: Option<MemoryAccess>,
+    /// Set for taken branches to the PC fetching must continue from.
+    #[serde(rename = "ForwardingRedirectPC")]
+    pub forwarding_redirect_pc: Option<u64>,
+    /// Set when the forwarded result is the one of a fused `divu`/`remu` pair.
+    #[serde(rename = "ForwardingFused")]
+    pub forwarding_fused: Option<FusedResult>,
+    #[serde(skip_serializing)] // provenance of the forwarded result, for debugging
+    pub forwarding_op_code: String,
+    #[serde(skip_serializing)]
+    pub forwarding_op_a_value: u64,
+    #[serde(skip_serializing)]
+    pub forwarding_op_b_value: u64,
+    #[serde(skip_serializing)] // configuration rather than state
+    trap_on_overflow: bool,
 }
 
 impl ALU {
-    pub fn new() -> ALU {
+    /// Creates an idle ALU of `stage_count` execute stages, at least two, whose results are
+    /// forwarded `stage_count - 1` cycles after issue. When `trap_on_overflow` is set, unsigned
+    /// overflow of `add`, `addi` and `mulu`, and underflow of `sub` and `subi`, raise an exception
+    /// instead of wrapping around.
+    pub fn new(trap_on_overflow: bool, stage_count: usize) -> ALU {
         ALU {
-            stage1: None,
-            stage2: None,
+            stages: ALUStages(vec![None; stage_count.max(2)]),
             is_forwarding: false,
             forwarding_reg: 0,
             forwarding_value: 0,
             forwarding_pc: 0,
-            forwarding_exception: false,
+            forwarding_exception: None,
+            forwarding_writes_register: false,
+            forwarding_memory_access: None,
+            forwarding_redirect_pc: None,
+            forwarding_fused: None,
+            forwarding_op_code: String::new(),
+            forwarding_op_a_value: 0,
+            forwarding_op_b_value: 0,
+            trap_on_overflow,
         }
     }
 
+    /// Checks if the first stage is occupied, which keeps another instruction from being issued.
     pub fn is_busy(&self) -> bool {
-        self.stage1.is_some()
+        self.stages.0[0].is_some()
     }
 
-    pub fn latch(&mut self, entry: IntegerQueueEntry) {
+    /// Checks if none of the stages holds an instruction.
+    pub fn is_idle(&self) -> bool {
+        self.stages.0.iter().all(|stage| stage.is_none())
+    }
+
+    /// Checks if the instruction of the given PC is in one of the stages, alone or fused.
+    pub fn holds(&self, pc: u64) -> bool {
+        self.stages.0.iter().flatten().any(|entry| {
+            entry.pc == pc || entry.fused_partner.as_ref().is_some_and(|partner| partner.pc == pc)
+        })
+    }
+
+    /// Latches an instruction into stage 1, where it stays for `latency` cycles before moving on
+    /// to the following stages. The ALU remains busy for that whole time.
+    pub fn latch(&mut self, entry: IntegerQueueEntry, latency: usize) {
         if !self.is_busy() {
-            self.stage1 = Some(ALUEntry::new(
-                entry.dest_register,
-                entry.op_a_value,
-                entry.op_b_value,
-                entry.op_code,
-                entry.pc,
-            ));
+            self.stages.0[0] = Some(ALUEntry::new(entry, latency.saturating_sub(1)));
         } else {
             panic!("ALU stage 1 is already occupied");
         }
     }
 
+    /// Latches a `divu` or `remu` together with its partner reading the same operands, so a single
+    /// division of `latency` cycles produces both the quotient and the remainder.
+    pub fn latch_fused(
+        &mut self,
+        entry: IntegerQueueEntry,
+        partner: IntegerQueueEntry,
+        latency: usize,
+    ) {
+        self.latch(entry, latency);
+        if let Some(stage1_entry) = self.stages.0[0].as_mut() {
+            stage1_entry.fused_partner = Some(Box::new(ALUEntry::new(partner, 0)));
+        }
+    }
+
+    /// Moves every instruction one stage forward, forwarding the result of the one reaching the
+    /// last stage. The instruction of stage 1 only moves on once its latency elapsed.
     pub fn execute(&mut self) {
-        if self.stage2.is_some() {
-            self.stage2 = None;
+        let stages = &mut self.stages.0;
+        let last_stage = stages.len() - 1;
+        // The last stage was forwarded in the previous cycle, the middle stages move forward
+        stages[last_stage] = None;
+        for stage in (2..=last_stage).rev() {
+            stages[stage] = stages[stage - 1].take();
+        }
+        match stages[0].as_mut() {
+            // No result is produced while the operation is in flight
+            Some(stage1_entry) if stage1_entry.remaining_cycles > 0 => {
+                stage1_entry.remaining_cycles -= 1;
+            }
+            _ => stages[1] = stages[0].take(),
         }
-        if self.stage1.is_some() {
-            self.stage2 = self.stage1.take();
-            self.update_forwarding_state(); // Update forwarding values directly after stage 2 is occupied
+        // Only a result produced in this cycle is put on the forwarding path
+        self.clear_forwarding_state();
+        if self.stages.0[last_stage].is_some() {
+            self.update_forwarding_state(); // Update forwarding values directly after the last stage is occupied
         }
     }
 
     pub fn reset(&mut self) {
-        self.stage1 = None;
-        self.stage2 = None;
+        self.stages.0.fill(None);
+        self.clear_forwarding_state();
+    }
+
+    /// Removes every instruction younger than `pc` from the stages. A result being forwarded is
+    /// recomputed from what is left in the last stage, if it belonged to a removed instruction.
+    pub fn squash_younger_than(&mut self, pc: u64) {
+        for stage in self.stages.0.iter_mut() {
+            *stage = stage.take().and_then(|entry| entry.squash_younger_than(pc));
+        }
+        let forwards_squashed_result = self.is_forwarding
+            && (self.forwarding_pc > pc
+                || self.forwarding_fused.as_ref().is_some_and(|fused| fused.pc > pc));
+        if forwards_squashed_result {
+            self.clear_forwarding_state();
+            if self.stages.0.last().is_some_and(|stage| stage.is_some()) {
+                self.update_forwarding_state();
+            }
+        }
+    }
+
+    fn clear_forwarding_state(&mut self) {
         self.is_forwarding = false;
         self.forwarding_reg = 0;
         self.forwarding_value = 0;
         self.forwarding_pc = 0;
-        self.forwarding_exception = false;
+        self.forwarding_exception = None;
+        self.forwarding_writes_register = false;
+        self.forwarding_memory_access = None;
+        self.forwarding_redirect_pc = None;
+        self.forwarding_fused = None;
+        self.forwarding_op_code.clear();
+        self.forwarding_op_a_value = 0;
+        self.forwarding_op_b_value = 0;
     }
 
     fn compute(&mut self, stage1_entry: &ALUEntry) -> u64 {
         match stage1_entry.op_code.as_str() {
-            "add" => self.wrapping_op(stage1_entry, u64::wrapping_add),
-            "sub" => self.wrapping_op(stage1_entry, u64::wrapping_sub),
-            "mulu" => self.wrapping_op(stage1_entry, u64::wrapping_mul),
+            "add" if stage1_entry.is_immediate => self.addi_op(stage1_entry),
+            "add" => self.overflowing_op(stage1_entry, u64::overflowing_add),
+            "sub" => self.sub_op(stage1_entry),
+            "mulu" => self.overflowing_op(stage1_entry, u64::overflowing_mul),
             "divu" => self.division_op(stage1_entry),
             "remu" => self.modulo_op(stage1_entry),
-            "addi" => self.addi_op(stage1_entry),
+            "div" => self.signed_division_op(stage1_entry, i64::checked_div),
+            "rem" => self.signed_division_op(stage1_entry, i64::checked_rem),
+            // Bitwise operations can neither overflow nor divide by zero, so they never raise an
+            // exception.
+            "and" => self.wrapping_op(stage1_entry, |a, b| a & b),
+            "or" => self.wrapping_op(stage1_entry, |a, b| a | b),
+            "xor" => self.wrapping_op(stage1_entry, |a, b| a ^ b),
+            // Shift amounts only use the low six bits of operand B, as in RV64.
+            "sll" => self.wrapping_op(stage1_entry, |a, b| a << (b & 63)),
+            "srl" => self.wrapping_op(stage1_entry, |a, b| a >> (b & 63)),
+            "sra" => self.wrapping_op(stage1_entry, |a, b| ((a as i64) >> (b & 63)) as u64),
+            // Comparisons produce 1 when operand A is less than operand B, 0 otherwise.
+            "slt" => self.wrapping_op(stage1_entry, |a, b| ((a as i64) < (b as i64)) as u64),
+            "sltu" => self.wrapping_op(stage1_entry, |a, b| (a < b) as u64),
+            "ld" => self.load_op(stage1_entry),
+            "st" => self.store_op(stage1_entry),
+            "beq" => self.branch_op(stage1_entry, |a, b| a == b),
+            "bne" => self.branch_op(stage1_entry, |a, b| a != b),
+            "jal" => self.jump_op(stage1_entry),
+            "lui" => stage1_entry.op_b_value << LUI_SHIFT,
             _ => panic!("Invalid op code"),
         }
     }
@@ -212,50 +533,165 @@ impl ALU {
         op(entry.op_a_value, entry.op_b_value)
     }
 
-    fn division_op(&mut self, entry: &ALUEntry) -> u64 {
-        if entry.op_b_value == 0 {
-            self.forwarding_exception = true;
-            0
+    /// Applies an operation that reports whether it overflowed. On overflow, the forwarding
+    /// exception is raised and 0 is produced if the ALU traps, otherwise the result wraps around.
+    fn overflowing_op<F>(&mut self, entry: &ALUEntry, op: F) -> u64
+    where
+        F: Fn(u64, u64) -> (u64, bool),
+    {
+        let (result, overflow) = op(entry.op_a_value, entry.op_b_value);
+        if overflow && self.trap_on_overflow {
+            self.raise(ExceptionCause::Overflow)
         } else {
-            entry.op_a_value / entry.op_b_value
+            result
+        }
+    }
+
+    /// Subtracts operand B, or the sign-extended immediate. When the ALU traps, a result below zero
+    /// raises an underflow, and a negative immediate pushing the result past `u64::MAX` raises an
+    /// overflow. Otherwise the result wraps around.
+    fn sub_op(&mut self, entry: &ALUEntry) -> u64 {
+        let (result, out_of_range) = if entry.is_immediate {
+            let immediate = entry.op_b_value as i64;
+            entry.op_a_value.overflowing_add_signed(immediate.wrapping_neg())
+        } else {
+            entry.op_a_value.overflowing_sub(entry.op_b_value)
+        };
+        if !(out_of_range && self.trap_on_overflow) {
+            result
+        } else if entry.is_immediate && (entry.op_b_value as i64) < 0 {
+            self.raise(ExceptionCause::Overflow)
+        } else {
+            self.raise(ExceptionCause::Underflow)
+        }
+    }
+
+    fn division_op(&mut self, entry: &ALUEntry) -> u64 {
+        match entry.op_a_value.checked_div(entry.op_b_value) {
+            Some(result) => result,
+            None => self.raise(ExceptionCause::DivideByZero),
         }
     }
 
     fn modulo_op(&mut self, entry: &ALUEntry) -> u64 {
         if entry.op_b_value == 0 {
-            self.forwarding_exception = true;
-            0
+            self.raise(ExceptionCause::DivideByZero)
         } else {
             entry.op_a_value % entry.op_b_value
         }
     }
 
-    fn addi_op(&self, entry: &ALUEntry) -> u64 {
-        let immediate = entry.op_b_value as i64 as u64;
-        entry.op_a_value.wrapping_add(immediate)
+    /// Applies a signed division or remainder to the operands reinterpreted as `i64`. Dividing by
+    /// zero or `i64::MIN / -1`, whose quotient does not fit, raise the forwarding exception.
+    fn signed_division_op<F>(&mut self, entry: &ALUEntry, op: F) -> u64
+    where
+        F: Fn(i64, i64) -> Option<i64>,
+    {
+        match op(entry.op_a_value as i64, entry.op_b_value as i64) {
+            Some(result) => result as u64,
+            None if entry.op_b_value == 0 => self.raise(ExceptionCause::DivideByZero),
+            None => self.raise(ExceptionCause::Overflow),
+        }
+    }
+
+    /// Raises the forwarding exception with the given cause, producing 0.
+    fn raise(&mut self, cause: ExceptionCause) -> u64 {
+        self.forwarding_exception = Some(cause);
+        0
+    }
+
+    /// Adds the sign-extended immediate, so a negative immediate only overflows when the result
+    /// would drop below zero.
+    fn addi_op(&mut self, entry: &ALUEntry) -> u64 {
+        self.overflowing_op(entry, |a, immediate| a.overflowing_add_signed(immediate as i64))
+    }
+
+    /// Computes the load address, the loaded value is only read from memory at commit.
+    fn load_op(&mut self, entry: &ALUEntry) -> u64 {
+        let address = entry.op_a_value.wrapping_add(entry.op_b_value);
+        self.forwarding_memory_access = Some(MemoryAccess::Load { address });
+        0
+    }
+
+    /// Computes the store address, the value is only written to memory at commit.
+    fn store_op(&mut self, entry: &ALUEntry) -> u64 {
+        let address = entry.op_a_value.wrapping_add(entry.offset);
+        self.forwarding_memory_access = Some(MemoryAccess::Store {
+            address,
+            value: entry.op_b_value,
+        });
+        0
+    }
+
+    /// Resolves a branch, redirecting fetch to its target when the condition holds.
+    fn branch_op<F>(&mut self, entry: &ALUEntry, condition: F) -> u64
+    where
+        F: Fn(u64, u64) -> bool,
+    {
+        if condition(entry.op_a_value, entry.op_b_value) {
+            self.forwarding_redirect_pc = Some(entry.offset);
+        }
+        0
+    }
+
+    /// Redirects fetch to the jump target, returning the address of the following instruction.
+    fn jump_op(&mut self, entry: &ALUEntry) -> u64 {
+        self.forwarding_redirect_pc = Some(entry.offset);
+        entry.pc + 1
     }
 
     fn update_forwarding_state(&mut self) {
-        let stage2_entry = self.stage2.as_ref().unwrap().clone();
+        let last_stage_entry = self.stages.0.last().cloned().flatten().unwrap();
         self.is_forwarding = true;
-        self.forwarding_reg = stage2_entry.dest_register;
-        self.forwarding_pc = stage2_entry.pc;
-        self.forwarding_value = self.compute(&stage2_entry);
+        self.forwarding_reg = last_stage_entry.dest_register;
+        self.forwarding_pc = last_stage_entry.pc;
+        self.forwarding_value = self.compute(&last_stage_entry);
+        self.forwarding_writes_register =
+            last_stage_entry.writes_register && self.forwarding_memory_access.is_none();
+        self.forwarding_op_code = last_stage_entry.op_code.clone();
+        self.forwarding_op_a_value = last_stage_entry.op_a_value;
+        self.forwarding_op_b_value = last_stage_entry.op_b_value;
+        if let Some(partner) = last_stage_entry.fused_partner.as_deref() {
+            // The partner may raise its own exception, kept apart from the one of the entry
+            let exception = self.forwarding_exception.take();
+            let value = self.compute(partner);
+            self.forwarding_fused = Some(FusedResult {
+                reg: partner.dest_register,
+                value,
+                pc: partner.pc,
+                exception: self.forwarding_exception.take(),
+                op_code: partner.op_code.clone(),
+            });
+            self.forwarding_exception = exception;
+        }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct DecodedInstruction {
+    #[serde(rename = "PC")]
     pub pc: u64,
+    #[serde(rename = "OpCode")]
     pub op_code: String,
+    #[serde(rename = "IsImmediate")]
     pub immediate: bool,
+    #[serde(rename = "LogicalDestination")]
     pub logical_destination: u8,
+    #[serde(rename = "OpARegTag")]
     pub op_a_reg_tag: u8,
+    #[serde(rename = "OpBRegTag")]
     pub op_b_reg_tag: u8,
-    pub immediate_value: u32,
+    #[serde(rename = "ImmediateValue")]
+    pub immediate_value: i64,
+    #[serde(rename = "WritesRegister")]
+    pub writes_register: bool,
+    /// Op code as written in the program, e.g. "addi" for an instruction executed as "add".
+    #[serde(skip)]
+    pub mnemonic: String,
 }
 
 impl DecodedInstruction {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         pc: u64,
         op_code: String,
@@ -263,16 +699,74 @@ impl DecodedInstruction {
         logical_destination: u8,
         op_a_reg_tag: u8,
         op_b_reg_tag: u8,
-        immediate_value: u32,
+        immediate_value: i64,
+        writes_register: bool,
     ) -> DecodedInstruction {
         DecodedInstruction {
             pc,
+            mnemonic: op_code.clone(),
             op_code,
             immediate,
             logical_destination,
             op_a_reg_tag,
             op_b_reg_tag,
             immediate_value,
+            writes_register,
+        }
+    }
+
+    /// Checks if the instruction is a `nop`, which needs neither an integer queue entry nor an ALU.
+    pub fn is_nop(&self) -> bool {
+        self.op_code == NOP_OP_CODE
+    }
+
+    /// Checks if the instruction ends the program when it retires. Like a `nop`, it needs neither
+    /// an integer queue entry nor an ALU.
+    pub fn is_halt(&self) -> bool {
+        HALT_OP_CODES.contains(&self.op_code.as_str())
+    }
+
+    /// Checks if the instruction is executed by an ALU, rather than being done on dispatch.
+    pub fn needs_alu(&self) -> bool {
+        !self.is_nop() && !self.is_halt()
+    }
+
+    /// Checks if operand A is read from a register. A jump or a load upper immediate has no
+    /// register source, its operand A tag is only a placeholder.
+    pub fn reads_operand_a(&self) -> bool {
+        self.op_code != JUMP_OP_CODE && self.op_code != LUI_OP_CODE
+    }
+}
+
+/// Formats the instruction back into assembly, using the immediate form of the op code when the
+/// operand B is an immediate.
+///
+/// ex: "add" with immediate 5 -> "addi x1, x2, 5"
+/// ex: "ld" -> "ld x1, 8(x2)"
+impl fmt::Display for DecodedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op_code = self.op_code.as_str();
+        let (rd, rs1, rs2) = (
+            self.logical_destination,
+            self.op_a_reg_tag,
+            self.op_b_reg_tag,
+        );
+        let immediate = self.immediate_value;
+        match op_code {
+            _ if !self.needs_alu() => write!(f, "{}", op_code),
+            "ld" => write!(f, "ld x{}, {}(x{})", rd, immediate, rs1),
+            "st" => write!(f, "st x{}, {}(x{})", rs2, immediate, rs1),
+            _ if BRANCH_OP_CODES.contains(&op_code) => {
+                write!(f, "{} x{}, x{}, {}", op_code, rs1, rs2, immediate)
+            }
+            JUMP_OP_CODE | LUI_OP_CODE => write!(f, "{} x{}, {}", op_code, rd, immediate),
+            "sltu" if self.immediate => write!(
+                f,
+                "{} x{}, x{}, {}",
+                SLTU_IMMEDIATE_OP_CODE, rd, rs1, immediate
+            ),
+            _ if self.immediate => write!(f, "{}i x{}, x{}, {}", op_code, rd, rs1, immediate),
+            _ => write!(f, "{} x{}, x{}, x{}", op_code, rd, rs1, rs2),
         }
     }
 }
@@ -286,13 +780,125 @@ impl Instruction {
         Instruction { value }
     }
 
+    /// Returns the name of the label defined by an entry like "loop:", if the entry is one.
+    pub fn label_definition(entry: &str) -> Option<&str> {
+        entry
+            .trim()
+            .strip_suffix(':')
+            .filter(|label| Instruction::is_label(label))
+    }
+
+    /// Returns the label targeted by a branch or jump, when its target is a name rather than a PC.
+    ///
+    /// ex: "bne x1, x2, loop" -> Some("loop")
+    pub fn target_label(&self) -> Option<&str> {
+        let mut parts = self
+            .value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty());
+        let op_code = parts.next()?.to_lowercase();
+        if !BRANCH_OP_CODES.contains(&op_code.as_str()) && op_code != JUMP_OP_CODE {
+            return None;
+        }
+        parts.next_back().filter(|target| Instruction::is_label(target))
+    }
+
+    /// Returns the instruction with its target label replaced by the given PC.
+    pub fn with_target(&self, target: u64) -> Instruction {
+        let mut value = self.value.clone();
+        if let Some(label) = self.target_label() {
+            // The target is the last operand, so the last occurrence of the label is the target
+            let index = self.value.rfind(label).unwrap_or_default();
+            value.replace_range(index..index + label.len(), &target.to_string());
+        }
+        Instruction::new(value)
+    }
+
+    /// Checks if the instruction is the `li` pseudo-instruction, which reads x0.
+    pub fn is_load_immediate(&self) -> bool {
+        self.value
+            .split_whitespace()
+            .next()
+            .is_some_and(|op_code| op_code.eq_ignore_ascii_case(LOAD_IMMEDIATE_OP_CODE))
+    }
+
+    /// Checks if a name can be a label: a letter or underscore followed by letters, digits or
+    /// underscores.
+    fn is_label(name: &str) -> bool {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    /// Returns the raw assembly text of the instruction.
+    pub fn text(&self) -> &str {
+        &self.value
+    }
+
     /// Decodes an assembly instruction string into its components.
     ///
     /// ex: "add x0, x1, x2" -> DecodedInstruction
     /// ex: "addi x0, x1, 10" -> DecodedInstruction with immediate value
+    /// ex: "addi x0, x1, -5" -> DecodedInstruction with signed immediate value
+    /// ex: "subi x0, x1, 3" -> DecodedInstruction of a "sub" with immediate value
+    /// ex: "ld x0, 8(x1)" -> DecodedInstruction of a load from address x1 + 8
+    /// ex: "beq x0, x1, 4" -> DecodedInstruction of a branch to instruction 4
+    /// ex: "jal x1, 5" -> DecodedInstruction of a jump to instruction 5 linking to x1
+    /// ex: "ADDI x0, x1, 10" -> same DecodedInstruction as "addi x0, x1, 10"
+    /// ex: "nop" -> DecodedInstruction writing no register
+    /// ex: "halt" -> DecodedInstruction ending the program
+    /// ex: "mv x0, x1" -> same DecodedInstruction as "addi x0, x1, 0"
+    /// ex: "li x1, 42" -> same DecodedInstruction as "addi x1, x0, 42"
     pub fn decode(&self, pc: u64) -> Result<DecodedInstruction, &'static str> {
-        let instruction_minified = self.value.replace(",", "");
-        let parts: Vec<&str> = instruction_minified.split_whitespace().collect();
+        let mut parts = Instruction::tokenize(&self.value)?;
+        // Op codes are case-insensitive, they are matched in lowercase
+        let op_code_lowercase = parts.first().map(|op_code| op_code.to_lowercase());
+        if let (Some(op_code), Some(lowercase)) = (parts.first_mut(), &op_code_lowercase) {
+            *op_code = lowercase;
+        }
+        if parts.first().is_some_and(|op_code| MEMORY_OP_CODES.contains(op_code)) {
+            return Instruction::decode_memory_access(&parts, pc);
+        }
+        if parts.first().is_some_and(|op_code| BRANCH_OP_CODES.contains(op_code)) {
+            return Instruction::decode_branch(&parts, pc);
+        }
+        if parts.first() == Some(&JUMP_OP_CODE) {
+            return Instruction::decode_jump(&parts, pc);
+        }
+        if parts.first() == Some(&LUI_OP_CODE) {
+            return Instruction::decode_upper_immediate(&parts, pc);
+        }
+        if parts.first().is_some_and(|op_code| {
+            *op_code == NOP_OP_CODE || HALT_OP_CODES.contains(op_code)
+        }) {
+            if parts.len() != 1 {
+                return Err("Invalid instruction format");
+            }
+            return Ok(DecodedInstruction::new(
+                pc,
+                parts[0].to_string(),
+                false,
+                0,
+                0,
+                0,
+                0,
+                false,
+            ));
+        }
+        if parts.first() == Some(&MOVE_OP_CODE) {
+            if parts.len() != 3 {
+                return Err("Invalid instruction format");
+            }
+            parts = vec!["addi", parts[1], parts[2], "0"];
+        }
+        if parts.first() == Some(&LOAD_IMMEDIATE_OP_CODE) {
+            if parts.len() != 3 {
+                return Err("Invalid instruction format");
+            }
+            parts = vec!["addi", parts[1], "x0", parts[2]];
+        }
         if parts.len() != 4 {
             return Err("Invalid instruction format");
         }
@@ -300,8 +906,14 @@ impl Instruction {
         let mut op_code = parts[0];
         let is_immediate = IMMEDIATE_OP_CODES.contains(&op_code);
 
-        if IMMEDIATE_OP_CODES.contains(&op_code) {
-            op_code = "add"; // "addi" is treated as "add" for the purpose of this simulation
+        if is_immediate {
+            // Immediate forms are treated as their register counterpart for the purpose of this
+            // simulation, e.g. "addi" becomes "add" and "subi" becomes "sub".
+            op_code = if op_code == SLTU_IMMEDIATE_OP_CODE {
+                "sltu"
+            } else {
+                &op_code[..op_code.len() - 1]
+            };
         }
 
         if !ALLOWED_OP_CODES.contains(&op_code) {
@@ -312,19 +924,18 @@ impl Instruction {
         let op_a_reg_tag = Instruction::parse_register(parts[2])?;
 
         let op_b_reg_tag: u8;
-        let immediate_value: u32;
+        let immediate_value: i64;
 
         if is_immediate {
-            immediate_value = parts[3]
-                .parse::<u32>()
-                .map_err(|_| "Invalid immediate value")?;
+            immediate_value =
+                Instruction::parse_immediate(parts[3]).ok_or("Invalid immediate value")?;
             op_b_reg_tag = 0; // Immediate instructions don't use a second register
         } else {
             op_b_reg_tag = Instruction::parse_register(parts[3])?;
             immediate_value = 0; // Non-immediate instructions don't have an immediate value
         }
 
-        Ok(DecodedInstruction::new(
+        let mut decoded = DecodedInstruction::new(
             pc,
             op_code.to_string(),
             is_immediate,
@@ -332,13 +943,526 @@ impl Instruction {
             op_a_reg_tag,
             op_b_reg_tag,
             immediate_value,
+            true,
+        );
+        decoded.mnemonic = parts[0].to_string();
+        Ok(decoded)
+    }
+
+    /// Splits an instruction into its op code followed by its operands. The op code is separated
+    /// from the operands by whitespace, and the operands by commas, whitespace or both.
+    /// Fails on a comma without an operand before or after it, and on an op code run together with
+    /// its operands.
+    ///
+    /// ex: "add\tx1,x2 , x3" -> ["add", "x1", "x2", "x3"]
+    /// ex: "addx1, x2, x3" -> Err
+    fn tokenize(instruction: &str) -> Result<Vec<&str>, &'static str> {
+        let instruction = instruction.trim();
+        let (op_code, operands) = instruction
+            .split_once(char::is_whitespace)
+            .unwrap_or((instruction, ""));
+        if op_code.contains(',') {
+            return Err("Missing separator between op code and operands");
+        }
+        let mut parts = vec![op_code];
+        if operands.trim().is_empty() {
+            return Ok(parts);
+        }
+        for operand_group in operands.split(',') {
+            let group: Vec<&str> = operand_group.split_whitespace().collect();
+            if group.is_empty() {
+                return Err("Missing operand between commas");
+            }
+            parts.extend(group);
+        }
+        Ok(parts)
+    }
+
+    /// Decodes a load or store, whose address operand uses the "imm(reg)" syntax.
+    ///
+    /// ex: "ld x1, 8(x2)" -> immediate "ld" of operand A x2 and immediate 8, writing x1
+    /// ex: "st x1, 8(x2)" -> "st" of operand A x2 and operand B x1 with offset 8, writing nothing
+    fn decode_memory_access(parts: &[&str], pc: u64) -> Result<DecodedInstruction, &'static str> {
+        if parts.len() != 3 {
+            return Err("Invalid instruction format");
+        }
+        let register = Instruction::parse_register(parts[1])?;
+        let (offset, base_register) = Instruction::parse_address(parts[2])?;
+
+        if parts[0] == "ld" {
+            Ok(DecodedInstruction::new(
+                pc,
+                parts[0].to_string(),
+                true,
+                register,
+                base_register,
+                0,
+                offset,
+                true,
+            ))
+        } else {
+            // Stores have no destination, the logical destination is left unused
+            Ok(DecodedInstruction::new(
+                pc,
+                parts[0].to_string(),
+                false,
+                0,
+                base_register,
+                register,
+                offset,
+                false,
+            ))
+        }
+    }
+
+    /// Decodes a conditional branch, which compares two registers and writes nothing.
+    ///
+    /// ex: "bne x1, x2, 0" -> "bne" of operand A x1 and operand B x2 with target 0
+    fn decode_branch(parts: &[&str], pc: u64) -> Result<DecodedInstruction, &'static str> {
+        if parts.len() != 4 {
+            return Err("Invalid instruction format");
+        }
+        let op_a_reg_tag = Instruction::parse_register(parts[1])?;
+        let op_b_reg_tag = Instruction::parse_register(parts[2])?;
+        let target = Instruction::parse_target(parts[3])?;
+
+        Ok(DecodedInstruction::new(
+            pc,
+            parts[0].to_string(),
+            false,
+            0,
+            op_a_reg_tag,
+            op_b_reg_tag,
+            target,
+            false,
         ))
     }
 
-    /// Parses a register string (e.g., "x1") and returns the register number.
-    fn parse_register(reg_str: &str) -> Result<u8, &'static str> {
-        reg_str[1..]
+    /// Decodes a jump, whose destination receives the address of the following instruction.
+    /// The operands are unused, the target is carried as an immediate.
+    ///
+    /// ex: "jal x1, 5" -> "jal" of destination x1 with target 5
+    fn decode_jump(parts: &[&str], pc: u64) -> Result<DecodedInstruction, &'static str> {
+        if parts.len() != 3 {
+            return Err("Invalid instruction format");
+        }
+        let dest_register = Instruction::parse_register(parts[1])?;
+        let target = Instruction::parse_target(parts[2])?;
+
+        Ok(DecodedInstruction::new(
+            pc,
+            parts[0].to_string(),
+            true,
+            dest_register,
+            0,
+            0,
+            target,
+            true,
+        ))
+    }
+
+    /// Decodes a load upper immediate. The immediate may take up to 52 bits, signed or unsigned,
+    /// so that the shifted value fits in a register. Like for jumps, operand A is unused.
+    ///
+    /// ex: "lui x1, 1048576" -> "lui" of destination x1 with immediate 1048576, writing 2^32
+    fn decode_upper_immediate(parts: &[&str], pc: u64) -> Result<DecodedInstruction, &'static str> {
+        if parts.len() != 3 {
+            return Err("Invalid instruction format");
+        }
+        let dest_register = Instruction::parse_register(parts[1])?;
+        let max_immediate = 1i64 << (u64::BITS - LUI_SHIFT);
+        let immediate = Instruction::parse_immediate(parts[2])
+            .filter(|immediate| (-max_immediate / 2..max_immediate).contains(immediate))
+            .ok_or("Invalid immediate value")?;
+
+        Ok(DecodedInstruction::new(
+            pc,
+            parts[0].to_string(),
+            true,
+            dest_register,
+            0,
+            0,
+            immediate,
+            true,
+        ))
+    }
+
+    /// Parses an immediate, in decimal or in hexadecimal with a "0x" or "0X" prefix, both with an
+    /// optional leading minus sign. Hexadecimal immediates give the 64-bit pattern of the value,
+    /// so those above `i64::MAX` wrap to negative values.
+    ///
+    /// ex: "-5" -> -5
+    /// ex: "0XFF" -> 255
+    /// ex: "0xFFFFFFFFFFFFFFFF" -> -1
+    pub fn parse_immediate(immediate_str: &str) -> Option<i64> {
+        let (is_negative, magnitude) = match immediate_str.strip_prefix('-') {
+            Some(magnitude) => (true, magnitude),
+            None => (false, immediate_str),
+        };
+        let Some(hex) = magnitude
+            .strip_prefix("0x")
+            .or_else(|| magnitude.strip_prefix("0X"))
+        else {
+            return immediate_str.parse::<i64>().ok();
+        };
+        if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let value = u64::from_str_radix(hex, 16).ok()? as i64;
+        Some(if is_negative { value.wrapping_neg() } else { value })
+    }
+
+    /// Parses a branch target, the absolute index of an instruction in the program.
+    fn parse_target(target_str: &str) -> Result<i64, &'static str> {
+        target_str
+            .parse::<i64>()
+            .ok()
+            .filter(|target| *target >= 0)
+            .ok_or("Invalid branch target")
+    }
+
+    /// Parses an address operand (e.g., "-8(x2)") into its offset and base register.
+    fn parse_address(address_str: &str) -> Result<(i64, u8), &'static str> {
+        let (offset_str, register_str) = address_str
+            .strip_suffix(')')
+            .and_then(|address| address.split_once('('))
+            .ok_or("Invalid address operand")?;
+        let offset = if offset_str.is_empty() {
+            0
+        } else {
+            Instruction::parse_immediate(offset_str).ok_or("Invalid address offset")?
+        };
+        Ok((offset, Instruction::parse_register(register_str)?))
+    }
+
+    /// Parses a register string (e.g., "x1" or its ABI name "ra") and returns the register number.
+    /// Register names are case-insensitive.
+    pub fn parse_register(reg_str: &str) -> Result<u8, &'static str> {
+        let reg_str = reg_str.to_ascii_lowercase();
+        if reg_str == FRAME_POINTER_NAME {
+            return Ok(8);
+        }
+        if let Some(register) = ABI_REGISTER_NAMES.iter().position(|name| *name == reg_str) {
+            return Ok(register as u8);
+        }
+        let register = reg_str
+            .strip_prefix('x')
+            .ok_or("Invalid register identifier")?
             .parse::<u8>()
-            .map_err(|_| "Invalid register identifier")
+            .map_err(|_| "Invalid register identifier")?;
+        if register >= LOGICAL_REGISTER_COUNT {
+            return Err("Register index out of range");
+        }
+        Ok(register)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(instruction: &str) -> DecodedInstruction {
+        Instruction::new(instruction.to_string()).decode(0).unwrap()
+    }
+
+    /// Builds the integer queue entry of a decoded instruction whose register operands are ready
+    /// with the given values, as dispatch does.
+    fn queue_entry(
+        decoded: &DecodedInstruction,
+        op_a_value: u64,
+        op_b_value: u64,
+    ) -> IntegerQueueEntry {
+        let op_b_value = if decoded.immediate {
+            decoded.immediate_value as u64
+        } else {
+            op_b_value
+        };
+        IntegerQueueEntry::new(
+            decoded.logical_destination,
+            true,
+            decoded.op_a_reg_tag,
+            op_a_value,
+            true,
+            decoded.op_b_reg_tag,
+            op_b_value,
+            decoded.op_code.clone(),
+            decoded.pc,
+            decoded.immediate,
+            decoded.immediate_value as u64,
+            decoded.writes_register,
+        )
+    }
+
+    /// Executes a single instruction on an idle ALU of two stages, returning the ALU once it
+    /// forwards the result.
+    fn execute(instruction: &str, op_a_value: u64, op_b_value: u64, trap_on_overflow: bool) -> ALU {
+        let mut alu = ALU::new(trap_on_overflow, 2);
+        alu.latch(queue_entry(&decode(instruction), op_a_value, op_b_value), 1);
+        alu.execute();
+        assert!(alu.is_forwarding);
+        alu
+    }
+
+    #[test]
+    fn negative_addi_immediate_wraps_around_in_operand_b() {
+        let decoded = decode("addi x0, x1, -1");
+        assert_eq!(decoded.op_code, "add");
+        assert!(decoded.immediate);
+        assert_eq!(decoded.immediate_value, -1);
+        assert_eq!(queue_entry(&decoded, 0, 0).op_b_value, u64::MAX);
+
+        let alu = execute("addi x0, x1, -1", 5, 0, true);
+        assert_eq!(alu.forwarding_value, 4);
+        assert_eq!(alu.forwarding_exception, None);
+        assert_eq!(execute("addi x0, x1, -1", 0, 0, false).forwarding_value, u64::MAX);
+    }
+
+    #[test]
+    fn positive_addi_immediate_is_unchanged() {
+        let decoded = decode("addi x0, x1, 10");
+        assert_eq!(decoded.immediate_value, 10);
+        assert_eq!(queue_entry(&decoded, 0, 0).op_b_value, 10);
+        assert_eq!(execute("addi x0, x1, 10", 5, 0, true).forwarding_value, 15);
+    }
+
+    #[test]
+    fn subi_decodes_to_an_immediate_sub() {
+        let decoded = decode("subi x1, x2, 3");
+        assert_eq!(decoded.op_code, "sub");
+        assert_eq!(decoded.mnemonic, "subi");
+        assert!(decoded.immediate);
+        assert_eq!(decoded.immediate_value, 3);
+        assert_eq!(execute("subi x1, x2, 3", 10, 0, true).forwarding_value, 7);
+    }
+
+    #[test]
+    fn subi_below_zero_underflows() {
+        let alu = execute("subi x1, x2, 3", 2, 0, true);
+        assert_eq!(alu.forwarding_exception, Some(ExceptionCause::Underflow));
+        let alu = execute("subi x1, x2, 3", 2, 0, false);
+        assert_eq!(alu.forwarding_exception, None);
+        assert_eq!(alu.forwarding_value, u64::MAX);
+    }
+
+    #[test]
+    fn bitwise_operations_never_raise_an_exception() {
+        for (instruction, expected) in [
+            ("and x1, x2, x3", 0b1000),
+            ("or x1, x2, x3", 0b1110),
+            ("xor x1, x2, x3", 0b0110),
+        ] {
+            let alu = execute(instruction, 0b1100, 0b1010, true);
+            assert_eq!(alu.forwarding_value, expected, "{}", instruction);
+            assert_eq!(alu.forwarding_exception, None, "{}", instruction);
+        }
+        let alu = execute("xor x1, x2, x3", u64::MAX, 0, true);
+        assert_eq!(alu.forwarding_value, u64::MAX);
+        assert_eq!(alu.forwarding_exception, None);
+    }
+
+    #[test]
+    fn bitwise_immediates_decode_to_their_register_form() {
+        for (instruction, op_code, expected) in [
+            ("andi x1, x2, 6", "and", 0b0100),
+            ("ori x1, x2, 6", "or", 0b1110),
+            ("xori x1, x2, 6", "xor", 0b1010),
+        ] {
+            let decoded = decode(instruction);
+            assert_eq!(decoded.op_code, op_code);
+            assert!(decoded.immediate);
+            assert_eq!(execute(instruction, 0b1100, 0, true).forwarding_value, expected);
+        }
+    }
+
+    #[test]
+    fn arithmetic_right_shift_fills_with_ones_and_logical_with_zeros() {
+        let high_bit_set = 1 << 63;
+        assert_eq!(
+            execute("sra x1, x2, x3", high_bit_set, 4, true).forwarding_value,
+            0xF8 << 56
+        );
+        assert_eq!(
+            execute("srl x1, x2, x3", high_bit_set, 4, true).forwarding_value,
+            0x08 << 56
+        );
+        assert_eq!(execute("sll x1, x2, x3", 1, 4, true).forwarding_value, 16);
+    }
+
+    #[test]
+    fn shift_amounts_only_use_the_low_six_bits() {
+        assert_eq!(execute("sll x1, x2, x3", 1, 64 + 3, true).forwarding_value, 8);
+        assert_eq!(execute("srl x1, x2, x3", 8, 64 + 3, true).forwarding_value, 1);
+    }
+
+    #[test]
+    fn shift_immediates_decode_to_their_register_form() {
+        for (instruction, op_code) in [
+            ("slli x1, x2, 4", "sll"),
+            ("srli x1, x2, 4", "srl"),
+            ("srai x1, x2, 4", "sra"),
+        ] {
+            let decoded = decode(instruction);
+            assert_eq!(decoded.op_code, op_code);
+            assert!(decoded.immediate);
+            assert_eq!(decoded.immediate_value, 4);
+        }
+        assert_eq!(execute("srai x1, x2, 4", u64::MAX, 0, true).forwarding_value, u64::MAX);
+        assert_eq!(execute("srli x1, x2, 4", u64::MAX, 0, true).forwarding_value, u64::MAX >> 4);
+    }
+
+    /// Returns the number of cycles an idle ALU of two stages takes to forward the result of the
+    /// instruction latched with the given latency, checking it stays busy until then.
+    fn forwarding_cycle(instruction: &str, latency: usize) -> usize {
+        let mut alu = ALU::new(false, 2);
+        alu.latch(queue_entry(&decode(instruction), 8, 2), latency);
+        let mut cycle = 0;
+        while !alu.is_forwarding {
+            assert!(alu.is_busy());
+            alu.execute();
+            cycle += 1;
+        }
+        assert!(!alu.is_busy());
+        cycle
+    }
+
+    #[test]
+    fn multi_cycle_operation_forwards_after_its_latency() {
+        assert_eq!(forwarding_cycle("add x1, x2, x3", 1), 1);
+        assert_eq!(forwarding_cycle("mulu x1, x2, x3", 3), 3);
+        assert_eq!(forwarding_cycle("divu x1, x2, x3", 8), 8);
+    }
+
+    #[test]
+    fn multi_cycle_operation_forwards_its_result_once() {
+        let mut alu = ALU::new(false, 2);
+        alu.latch(queue_entry(&decode("divu x1, x2, x3"), 8, 2), 8);
+        let forwarded: Vec<u64> = (0..10)
+            .filter_map(|_| {
+                alu.execute();
+                alu.is_forwarding.then_some(alu.forwarding_value)
+            })
+            .collect();
+        assert_eq!(forwarded, [4]);
+    }
+
+    #[test]
+    fn unsigned_overflow_raises_an_exception_when_trapping() {
+        for (instruction, op_a_value, op_b_value) in [
+            ("add x1, x2, x3", u64::MAX, 1),
+            ("mulu x1, x2, x3", 1 << 32, 1 << 32),
+            ("addi x1, x2, 1", u64::MAX, 0),
+        ] {
+            let alu = execute(instruction, op_a_value, op_b_value, true);
+            assert_eq!(alu.forwarding_exception, Some(ExceptionCause::Overflow), "{}", instruction);
+            assert_eq!(alu.forwarding_value, 0, "{}", instruction);
+        }
+    }
+
+    #[test]
+    fn unsigned_overflow_wraps_around_when_not_trapping() {
+        let alu = execute("add x1, x2, x3", u64::MAX, 1, false);
+        assert_eq!(alu.forwarding_exception, None);
+        assert_eq!(alu.forwarding_value, 0);
+        let alu = execute("mulu x1, x2, x3", 1 << 32, (1 << 32) + 1, false);
+        assert_eq!(alu.forwarding_exception, None);
+        assert_eq!(alu.forwarding_value, 1 << 32);
+    }
+
+    #[test]
+    fn parse_register_rejects_malformed_and_out_of_range_registers() {
+        assert_eq!(Instruction::parse_register("x31"), Ok(31));
+        assert_eq!(
+            Instruction::parse_register("x32"),
+            Err("Register index out of range")
+        );
+        assert_eq!(
+            Instruction::parse_register("r5"),
+            Err("Invalid register identifier")
+        );
+        assert_eq!(Instruction::parse_register(""), Err("Invalid register identifier"));
+        assert_eq!(Instruction::parse_register("x"), Err("Invalid register identifier"));
+    }
+
+    #[test]
+    fn invalid_register_operands_are_decode_errors() {
+        for instruction in ["add x32, x1, x2", "add x1, r5, x2", "add x1, x2, ", "add x1, , x2"] {
+            assert!(
+                Instruction::new(instruction.to_string()).decode(0).is_err(),
+                "{}",
+                instruction
+            );
+        }
+    }
+
+    #[test]
+    fn idle_alu_stops_forwarding_the_previous_result() {
+        let mut alu = execute("addi x1, x2, 5", 1, 0, false);
+        assert_eq!((alu.forwarding_reg, alu.forwarding_value), (1, 6));
+
+        alu.execute();
+
+        assert!(alu.is_idle());
+        assert!(!alu.is_forwarding);
+        assert_eq!((alu.forwarding_reg, alu.forwarding_value, alu.forwarding_pc), (0, 0, 0));
+    }
+
+    #[test]
+    fn alu_does_not_forward_the_previous_result_while_the_next_is_in_flight() {
+        let mut alu = execute("addi x1, x2, 5", 1, 0, false);
+        let mut second = queue_entry(&decode("mulu x3, x4, x5"), 2, 3);
+        second.pc = 1;
+        alu.latch(second, 3);
+
+        let forwarded: Vec<(bool, u8, u64)> = (0..3)
+            .map(|_| {
+                alu.execute();
+                (alu.is_forwarding, alu.forwarding_reg, alu.forwarding_value)
+            })
+            .collect();
+
+        assert_eq!(forwarded, [(false, 0, 0), (false, 0, 0), (true, 3, 6)]);
+    }
+
+    #[test]
+    fn abi_register_names_map_to_their_index() {
+        for (name, index) in [
+            ("zero", 0),
+            ("ra", 1),
+            ("sp", 2),
+            ("s0", 8),
+            ("fp", 8),
+            ("s1", 9),
+            ("a0", 10),
+            ("s11", 27),
+            ("t6", 31),
+            ("A0", 10),
+        ] {
+            assert_eq!(Instruction::parse_register(name), Ok(index), "{}", name);
+        }
+        assert_eq!(decode("add sp, ra, t0"), decode("add x2, x1, x5"));
+    }
+
+    #[test]
+    fn unknown_register_names_are_rejected() {
+        for name in ["foo", "t7", "a8", "s12", "zeros"] {
+            assert_eq!(
+                Instruction::parse_register(name),
+                Err("Invalid register identifier"),
+                "{}",
+                name
+            );
+        }
+        assert!(Instruction::new("add x1, foo, x2".to_string()).decode(0).is_err());
+    }
+
+    #[test]
+    fn op_codes_decode_whatever_their_case() {
+        assert_eq!(decode("ADD x1, x2, x3"), decode("add x1, x2, x3"));
+        assert_eq!(decode("AddI x1, x2, 5"), decode("addi x1, x2, 5"));
+        assert_eq!(decode("SLTIU x1, x2, 5"), decode("sltiu x1, x2, 5"));
+        assert_eq!(decode("LD X1, 8(SP)"), decode("ld x1, 8(x2)"));
+        assert_eq!(decode("Bne x1, x2, 0"), decode("bne x1, x2, 0"));
+        assert!(decode("AddI x1, x2, 5").immediate);
+        assert!(!decode("ADD x1, x2, x3").immediate);
     }
 }
@@ -1,25 +1,398 @@
+use std::collections::VecDeque;
+
 use serde::Serialize;
 
 use crate::arch_modules::{
-    ActiveListEntry, ALU, CommitBufferEntry, DecodedInstruction, Instruction, IntegerQueueEntry,
+    ActiveListEntry, ALU, CommitBufferEntry, DecodedInstruction, ExceptionCause, Instruction,
+    IntegerQueueEntry, MemoryAccess,
+};
+use crate::statistics::{
+    InstructionMix, Occupancy, PeakOccupancy, PendingReport, PendingStatus, QueueWaitHistogram,
+    StallReport,
 };
+use crate::trace::{CycleTrace, ForwardEvent};
 
 const INITIAL_PC: u64 = 0;
 const INITIAL_EXCEPTION_PC: u64 = 0;
 const INTEGER_QUEUE_SIZE: usize = 32;
 const ACTIVE_LIST_SIZE: usize = 32;
-const BUSY_BIT_TABLE_SIZE: usize = 64;
 const PHYSICAL_REGISTER_FILE_SIZE: usize = 64;
 const REGISTER_MAP_TABLE_SIZE: u8 = 32;
-const START_OF_FREE_REGISTER_LIST: u8 = 32;
-const END_OF_FREE_REGISTER_LIST: u8 = 64;
 const DECODED_BUFFER_SIZE: usize = 4;
+const FETCH_WIDTH: usize = 4;
+const READ_PORTS: usize = 8;
 const ALU_COUNT: usize = 4;
+const ISSUE_WIDTH: usize = 4;
+const COMMIT_WIDTH: usize = 4;
+const ALU_STAGES: usize = 2;
+const MULU_LATENCY: usize = 1;
+const DIVISION_LATENCY: usize = 1;
+const FUSE_DIVMOD: bool = false;
+const FORWARDING_BUBBLE: bool = false;
+const INSTANT_ROLLBACK: bool = false;
+const COMPACTING_INTEGER_QUEUE: bool = false;
+const DEADLOCK_CYCLES: usize = 16;
+const INSTRUCTION_CYCLE_BUDGET: usize = 256;
+const TRAP_ON_OVERFLOW: bool = false;
+const HARDWIRED_ZERO_REGISTER: bool = false;
+const MEMORY_SIZE: usize = 64;
+const MEMORY_WORD_SIZE: u64 = 8;
+const ZERO_REGISTER: u8 = 0;
 const INITIAL_EXCEPTION_STATE: bool = false;
 const EXCEPTION_PC: u64 = 0x10000;
+/// Op codes executed by the divider, which may only be available in some of the ALUs.
+const DIVISION_OP_CODES: [&str; 4] = ["divu", "remu", "div", "rem"];
+
+/// Capacities of the processor structures, fixed for the lifetime of a `Processor`.
+/// The physical register file must hold more than the 32 architectural registers and at most 256
+/// registers, since register tags are stored as `u8`.
+#[derive(Clone)]
+pub struct ProcessorConfig {
+    pub integer_queue_size: usize,
+    /// Model the integer queue as an age-ordered array of slots that shifts the younger entries
+    /// down whenever one leaves, so the entries always fill the lowest slots, oldest first, and
+    /// dispatch allocates the slots right after the youngest one. When disabled, the default, a
+    /// dispatched instruction takes the lowest free slot and a freed slot stays empty until it is
+    /// reused, as in the R10000. Issue picks the oldest ready entry in both models.
+    pub compacting_integer_queue: bool,
+    pub active_list_size: usize,
+    pub physical_register_file_size: usize,
+    pub alu_count: usize,
+    /// Maximum number of instructions leaving the integer queue per cycle, bounded by the free ALUs.
+    pub issue_width: usize,
+    /// Number of execute stages of every ALU. Results are forwarded from the last stage,
+    /// `alu_stages - 1` cycles after issue, and a new instruction can be issued once the first
+    /// stage is free.
+    pub alu_stages: usize,
+    /// Maximum number of instructions retired, or rolled back in exception mode, per cycle.
+    pub commit_width: usize,
+    /// Roll back the whole active list in the first cycle of exception mode instead of
+    /// `commit_width` instructions per cycle. The recovered state is the same, only reached sooner,
+    /// which speeds up simulations that do not study the cost of exceptions.
+    pub instant_rollback: bool,
+    pub decoded_buffer_size: usize,
+    /// Number of instructions fetched and decoded per cycle, at most. Fetching also stops when the
+    /// decoded buffer is full, so a width below its size fills it over several cycles.
+    pub fetch_width: usize,
+    /// Number of register file reads per cycle. Dispatch captures the value of every ready register
+    /// operand through a read port, and stops for the cycle when the next instruction needs more
+    /// ports than are left. By default, a full decoded buffer never runs out of ports.
+    pub read_ports: usize,
+    /// Number of execute cycles of `mulu`. Other operations take a single cycle.
+    pub mulu_latency: usize,
+    /// Number of execute cycles of `divu`, `remu`, `div` and `rem`.
+    pub division_latency: usize,
+    /// Indices of the ALUs having a divider, the only ones `divu`, `remu`, `div` and `rem` are
+    /// issued to. When None, the default, every ALU can divide.
+    pub division_alus: Option<Vec<usize>>,
+    /// Delay the results forwarded from the ALUs to the integer queue by one cycle, so a dependent
+    /// instruction issues two cycles after its producer's result is produced instead of one.
+    /// Disabled by default, as given test 01 expects forwarding without bubble.
+    pub forwarding_bubble: bool,
+    /// Issue an adjacent `divu`/`remu` pair reading the same operands as a single division on one
+    /// ALU, forwarding the quotient and the remainder together.
+    pub fuse_divmod: bool,
+    /// Raise an exception on unsigned overflow of `add`, `addi` and `mulu`, and on underflow of
+    /// `sub` and `subi`, instead of wrapping. Division by zero traps in both modes.
+    /// Disabled by default, as given tests 03 to 05 expect wrapping arithmetic.
+    pub trap_on_overflow: bool,
+    /// Make x0 always read zero and discard writes to it, as in RISC-V. `li` needs it enabled.
+    /// Disabled by default, as given test 01 uses x0 as a general purpose register.
+    pub hardwired_zero_register: bool,
+    /// Number of 64-bit words of the data memory.
+    pub memory_size: usize,
+    /// PC fetching continues from once an exception is raised, the base of the exception handler.
+    /// When it lies within the program, the instructions from there run once the rollback is done.
+    pub exception_handler_pc: u64,
+    /// Value of the exception PC before any exception is raised.
+    pub initial_exception_pc: u64,
+    /// Number of consecutive cycles in which no instruction is fetched, dispatched, issued,
+    /// completed, committed or rolled back, after which the processor is reported as deadlocked.
+    /// An instruction executing in an ALU is not progress, so this must exceed the latencies.
+    pub deadlock_cycles: usize,
+    /// Number of cycles an instruction may spend in the active list without retiring, after which
+    /// the run is aborted with the reason it is stuck. Catches livelocks, in which the pipeline
+    /// keeps moving without the oldest instruction ever retiring.
+    pub instruction_cycle_budget: usize,
+    /// PC of the first instruction of the program, where fetching starts. The program is loaded
+    /// from there, one PC per instruction, and branch targets are absolute PCs in the same space.
+    pub initial_pc: u64,
+}
+
+impl Default for ProcessorConfig {
+    fn default() -> Self {
+        ProcessorConfig {
+            integer_queue_size: INTEGER_QUEUE_SIZE,
+            compacting_integer_queue: COMPACTING_INTEGER_QUEUE,
+            active_list_size: ACTIVE_LIST_SIZE,
+            physical_register_file_size: PHYSICAL_REGISTER_FILE_SIZE,
+            alu_count: ALU_COUNT,
+            issue_width: ISSUE_WIDTH,
+            alu_stages: ALU_STAGES,
+            commit_width: COMMIT_WIDTH,
+            instant_rollback: INSTANT_ROLLBACK,
+            decoded_buffer_size: DECODED_BUFFER_SIZE,
+            fetch_width: FETCH_WIDTH,
+            read_ports: READ_PORTS,
+            mulu_latency: MULU_LATENCY,
+            division_latency: DIVISION_LATENCY,
+            division_alus: None,
+            forwarding_bubble: FORWARDING_BUBBLE,
+            fuse_divmod: FUSE_DIVMOD,
+            trap_on_overflow: TRAP_ON_OVERFLOW,
+            hardwired_zero_register: HARDWIRED_ZERO_REGISTER,
+            memory_size: MEMORY_SIZE,
+            exception_handler_pc: EXCEPTION_PC,
+            initial_exception_pc: INITIAL_EXCEPTION_PC,
+            deadlock_cycles: DEADLOCK_CYCLES,
+            instruction_cycle_budget: INSTRUCTION_CYCLE_BUDGET,
+            initial_pc: INITIAL_PC,
+        }
+    }
+}
+
+impl ProcessorConfig {
+    /// Returns the number of execute cycles of the given op code.
+    pub fn latency(&self, op_code: &str) -> usize {
+        match op_code {
+            "mulu" => self.mulu_latency,
+            _ if DIVISION_OP_CODES.contains(&op_code) => self.division_latency,
+            _ => 1,
+        }
+    }
+
+    /// Checks if the given ALU can execute the given op code, i.e. it has a divider if the op code
+    /// is a division.
+    pub fn can_execute(&self, alu_index: usize, op_code: &str) -> bool {
+        match &self.division_alus {
+            Some(division_alus) if DIVISION_OP_CODES.contains(&op_code) => {
+                division_alus.contains(&alu_index)
+            }
+            _ => true,
+        }
+    }
+
+    /// Checks if writes to the logical register are discarded, i.e. it is a hardwired x0.
+    pub fn discards_writes_to(&self, logical_register: u8) -> bool {
+        self.hardwired_zero_register && logical_register == ZERO_REGISTER
+    }
+
+    /// Checks that a processor built from this configuration can make progress.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.physical_register_file_size <= REGISTER_MAP_TABLE_SIZE as usize {
+            return Err("Physical register file must be larger than the 32 logical registers");
+        }
+        if self.physical_register_file_size > u8::MAX as usize + 1 {
+            return Err("Physical register file cannot hold more than 256 registers");
+        }
+        if self.decoded_buffer_size == 0 {
+            return Err("Decoded buffer must hold at least one instruction");
+        }
+        if self.fetch_width == 0 {
+            return Err("Fetch width must be at least one instruction");
+        }
+        // Dispatch waits until every structure has room for a full decoded buffer.
+        if self.physical_register_file_size - (REGISTER_MAP_TABLE_SIZE as usize)
+            < self.decoded_buffer_size
+        {
+            return Err("Free list cannot hold a full decoded buffer");
+        }
+        if self.active_list_size < self.decoded_buffer_size {
+            return Err("Active list cannot hold a full decoded buffer");
+        }
+        if self.integer_queue_size < self.decoded_buffer_size {
+            return Err("Integer queue cannot hold a full decoded buffer");
+        }
+        // An instruction reads up to two operands, all through ports of the same cycle.
+        if self.read_ports < 2 {
+            return Err("Register file needs at least two read ports");
+        }
+        if self.alu_count == 0 {
+            return Err("Processor needs at least one ALU");
+        }
+        if let Some(division_alus) = &self.division_alus {
+            if division_alus.is_empty() {
+                return Err("At least one ALU must have a divider");
+            }
+            if division_alus.iter().any(|&alu_index| alu_index >= self.alu_count) {
+                return Err("Divider ALU index out of range");
+            }
+        }
+        if self.issue_width == 0 {
+            return Err("Issue width must be at least one instruction");
+        }
+        if self.commit_width == 0 {
+            return Err("Commit width must be at least one instruction");
+        }
+        if self.alu_stages < 2 {
+            return Err("ALUs need at least two stages");
+        }
+        if self.instruction_cycle_budget == 0 {
+            return Err("Instruction cycle budget must be at least one cycle");
+        }
+        if self.mulu_latency == 0 || self.division_latency == 0 {
+            return Err("Latencies must be at least one cycle");
+        }
+        Ok(())
+    }
+}
+
+/// Fluent alternative to filling in a `ProcessorConfig`, starting from the default configuration.
+///
+/// ex: ProcessorBuilder::new().alu_count(8).physical_registers(128).build()
+pub struct ProcessorBuilder {
+    config: ProcessorConfig,
+}
+
+impl Default for ProcessorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcessorBuilder {
+    pub fn new() -> ProcessorBuilder {
+        ProcessorBuilder {
+            config: ProcessorConfig::default(),
+        }
+    }
+
+    pub fn alu_count(mut self, alu_count: usize) -> ProcessorBuilder {
+        self.config.alu_count = alu_count;
+        self
+    }
+
+    pub fn issue_width(mut self, issue_width: usize) -> ProcessorBuilder {
+        self.config.issue_width = issue_width;
+        self
+    }
+
+    pub fn alu_stages(mut self, alu_stages: usize) -> ProcessorBuilder {
+        self.config.alu_stages = alu_stages;
+        self
+    }
+
+    pub fn integer_queue_size(mut self, integer_queue_size: usize) -> ProcessorBuilder {
+        self.config.integer_queue_size = integer_queue_size;
+        self
+    }
+
+    pub fn compacting_integer_queue(mut self, compacting_integer_queue: bool) -> ProcessorBuilder {
+        self.config.compacting_integer_queue = compacting_integer_queue;
+        self
+    }
+
+    pub fn active_list_size(mut self, active_list_size: usize) -> ProcessorBuilder {
+        self.config.active_list_size = active_list_size;
+        self
+    }
+
+    pub fn physical_registers(mut self, physical_registers: usize) -> ProcessorBuilder {
+        self.config.physical_register_file_size = physical_registers;
+        self
+    }
+
+    pub fn commit_width(mut self, commit_width: usize) -> ProcessorBuilder {
+        self.config.commit_width = commit_width;
+        self
+    }
+
+    pub fn instant_rollback(mut self, instant_rollback: bool) -> ProcessorBuilder {
+        self.config.instant_rollback = instant_rollback;
+        self
+    }
+
+    pub fn decoded_buffer_size(mut self, decoded_buffer_size: usize) -> ProcessorBuilder {
+        self.config.decoded_buffer_size = decoded_buffer_size;
+        self
+    }
+
+    pub fn fetch_width(mut self, fetch_width: usize) -> ProcessorBuilder {
+        self.config.fetch_width = fetch_width;
+        self
+    }
+
+    pub fn mulu_latency(mut self, mulu_latency: usize) -> ProcessorBuilder {
+        self.config.mulu_latency = mulu_latency;
+        self
+    }
+
+    pub fn division_latency(mut self, division_latency: usize) -> ProcessorBuilder {
+        self.config.division_latency = division_latency;
+        self
+    }
+
+    pub fn division_alus(mut self, division_alus: &[usize]) -> ProcessorBuilder {
+        self.config.division_alus = Some(division_alus.to_vec());
+        self
+    }
+
+    pub fn forwarding_bubble(mut self, forwarding_bubble: bool) -> ProcessorBuilder {
+        self.config.forwarding_bubble = forwarding_bubble;
+        self
+    }
+
+    pub fn fuse_divmod(mut self, fuse_divmod: bool) -> ProcessorBuilder {
+        self.config.fuse_divmod = fuse_divmod;
+        self
+    }
+
+    pub fn trap_on_overflow(mut self, trap_on_overflow: bool) -> ProcessorBuilder {
+        self.config.trap_on_overflow = trap_on_overflow;
+        self
+    }
+
+    pub fn read_ports(mut self, read_ports: usize) -> ProcessorBuilder {
+        self.config.read_ports = read_ports;
+        self
+    }
+
+    pub fn hardwired_zero_register(mut self, hardwired_zero_register: bool) -> ProcessorBuilder {
+        self.config.hardwired_zero_register = hardwired_zero_register;
+        self
+    }
+
+    pub fn memory_size(mut self, memory_size: usize) -> ProcessorBuilder {
+        self.config.memory_size = memory_size;
+        self
+    }
+
+    pub fn exception_handler_pc(mut self, exception_handler_pc: u64) -> ProcessorBuilder {
+        self.config.exception_handler_pc = exception_handler_pc;
+        self
+    }
+
+    pub fn initial_exception_pc(mut self, initial_exception_pc: u64) -> ProcessorBuilder {
+        self.config.initial_exception_pc = initial_exception_pc;
+        self
+    }
+
+    pub fn deadlock_cycles(mut self, deadlock_cycles: usize) -> ProcessorBuilder {
+        self.config.deadlock_cycles = deadlock_cycles;
+        self
+    }
+
+    pub fn instruction_cycle_budget(mut self, instruction_cycle_budget: usize) -> ProcessorBuilder {
+        self.config.instruction_cycle_budget = instruction_cycle_budget;
+        self
+    }
+
+    pub fn initial_pc(mut self, initial_pc: u64) -> ProcessorBuilder {
+        self.config.initial_pc = initial_pc;
+        self
+    }
+
+    /// Validates the configuration and builds the processor.
+    pub fn build(self) -> Result<Processor, &'static str> {
+        self.config.validate()?;
+        Ok(Processor::with_config(self.config))
+    }
+}
 
 #[derive(Clone, Serialize)]
 pub struct Processor {
+    #[serde(skip_serializing)] // skip serializing configuration
+    config: ProcessorConfig,
     #[serde(rename = "ActiveList")]
     active_list: Vec<ActiveListEntry>,
     #[serde(rename = "BusyBitTable")]
@@ -30,13 +403,19 @@ pub struct Processor {
     decoded_instructions: Vec<DecodedInstruction>,
     #[serde(rename = "Exception")]
     exception_mode: bool,
+    #[serde(rename = "ExceptionCause")]
+    exception_cause: Option<ExceptionCause>,
     #[serde(rename = "ExceptionPC")]
     exception_pc: u64,
+    // FIFO queue, serialized front to back
     #[serde(rename = "FreeList")]
-    free_list: Vec<u8>,
-    // FIFO queue
+    free_list: VecDeque<u8>,
+    // Sorted by PC, which is also the dispatch order
     #[serde(rename = "IntegerQueue")]
     integer_queue: Vec<IntegerQueueEntry>,
+    // Byte-addressable memory of 64-bit words, only written when a store retires
+    #[serde(rename = "Memory")]
+    memory: Vec<u64>,
     #[serde(skip_serializing)] // skip serializing ALUs
     alus: Vec<ALU>,
     #[serde(skip_serializing)] // skip serializing commit buffer
@@ -47,29 +426,360 @@ pub struct Processor {
     physical_register_file: Vec<u64>,
     #[serde(rename = "RegisterMapTable")]
     register_map_table: Vec<u8>,
+    #[serde(skip_serializing)] // a halt instruction retired, nothing is fetched anymore
+    halted: bool,
+    #[serde(skip_serializing)] // consecutive cycles without progress, for deadlock detection
+    idle_cycles: usize,
+    #[serde(skip_serializing)] // register file read ports used by dispatch this cycle
+    register_file_reads: usize,
+    #[serde(skip_serializing)] // results reaching the integer queue next cycle, with a bubble
+    delayed_forwards: Vec<ForwardedResult>,
+    #[serde(skip_serializing)] // cycles propagated since the initial state
+    cycle: u64,
+    #[serde(skip_serializing)] // PC the next retired instruction must have, if known
+    next_retired_pc: Option<u64>,
+    #[serde(skip_serializing)] // skip serializing statistics counters
+    committed_instructions: u64,
+    #[serde(skip_serializing)]
+    instruction_mix: InstructionMix,
+    #[serde(skip_serializing)]
+    stall_report: StallReport,
+    #[serde(skip_serializing)]
+    queue_waits: QueueWaitHistogram,
+    #[serde(skip_serializing)]
+    peak_occupancy: PeakOccupancy,
+    #[serde(skip_serializing)] // skip serializing the events of the last cycle
+    trace: CycleTrace,
+}
+
+/// Result forwarded from an ALU to the integer queue.
+#[derive(Clone, Copy)]
+struct ForwardedResult {
+    alu_index: usize,
+    register: u8,
+    value: u64,
+    producer_pc: u64,
+}
+
+/// Structure that ran out in the middle of dispatching the decoded buffer.
+enum DispatchStall {
+    FreeList,
+    ReadPorts,
+}
+
+/// Checkpoint of the full processor state, restored with `Processor::restore`.
+#[derive(Clone)]
+pub struct ProcessorSnapshot(Processor);
+
+impl Default for Processor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Processor {
     pub fn new() -> Processor {
+        Processor::with_config(ProcessorConfig::default())
+    }
+
+    /// Builds a processor from the given configuration, panicking if it is invalid.
+    /// Use `ProcessorBuilder` to get the validation error instead.
+    pub fn with_config(config: ProcessorConfig) -> Processor {
+        if let Err(e) = config.validate() {
+            panic!("Invalid processor configuration: {}", e);
+        }
         Processor {
-            active_list: Vec::with_capacity(ACTIVE_LIST_SIZE),
-            busy_bit_table: vec![false; BUSY_BIT_TABLE_SIZE],
-            decoded_pcs: Vec::with_capacity(DECODED_BUFFER_SIZE),
-            decoded_instructions: Vec::with_capacity(DECODED_BUFFER_SIZE),
+            active_list: Vec::with_capacity(config.active_list_size),
+            busy_bit_table: vec![false; config.physical_register_file_size],
+            decoded_pcs: Vec::with_capacity(config.decoded_buffer_size),
+            decoded_instructions: Vec::with_capacity(config.decoded_buffer_size),
             exception_mode: INITIAL_EXCEPTION_STATE,
-            exception_pc: INITIAL_EXCEPTION_PC,
-            free_list: (START_OF_FREE_REGISTER_LIST..END_OF_FREE_REGISTER_LIST).collect(),
-            integer_queue: Vec::with_capacity(INTEGER_QUEUE_SIZE),
-            alus: vec![ALU::new(); ALU_COUNT],
-            commit_buffer: Vec::with_capacity(ALU_COUNT),
-            pc: INITIAL_PC,
-            physical_register_file: vec![0; PHYSICAL_REGISTER_FILE_SIZE],
+            exception_cause: None,
+            exception_pc: config.initial_exception_pc,
+            free_list: (REGISTER_MAP_TABLE_SIZE as usize..config.physical_register_file_size)
+                .map(|register| register as u8)
+                .collect(),
+            integer_queue: Vec::with_capacity(config.integer_queue_size),
+            memory: vec![0; config.memory_size],
+            alus: vec![ALU::new(config.trap_on_overflow, config.alu_stages); config.alu_count],
+            commit_buffer: Vec::with_capacity(config.alu_count),
+            pc: config.initial_pc,
+            physical_register_file: vec![0; config.physical_register_file_size],
             register_map_table: (0..REGISTER_MAP_TABLE_SIZE).collect(),
+            halted: false,
+            idle_cycles: 0,
+            register_file_reads: 0,
+            delayed_forwards: Vec::new(),
+            cycle: 0,
+            next_retired_pc: None,
+            committed_instructions: 0,
+            instruction_mix: InstructionMix::default(),
+            stall_report: StallReport::default(),
+            queue_waits: QueueWaitHistogram::default(),
+            peak_occupancy: PeakOccupancy::default(),
+            trace: CycleTrace::default(),
+            config,
         }
     }
 
+    /// Restores the initial state of a processor built from the same configuration, keeping the
+    /// allocations of every structure. The data memory is cleared, so an initialized memory must
+    /// be loaded again.
+    pub fn reset(&mut self) {
+        self.active_list.clear();
+        self.busy_bit_table.fill(false);
+        self.clear_decoded_instructions();
+        self.exception_mode = INITIAL_EXCEPTION_STATE;
+        self.exception_cause = None;
+        self.exception_pc = self.config.initial_exception_pc;
+        self.free_list.clear();
+        self.free_list.extend(
+            (REGISTER_MAP_TABLE_SIZE as usize..self.config.physical_register_file_size)
+                .map(|register| register as u8),
+        );
+        self.reset_integer_queue();
+        self.memory.fill(0);
+        self.reset_alus();
+        self.commit_buffer.clear();
+        self.pc = self.config.initial_pc;
+        self.physical_register_file.fill(0);
+        self.register_map_table.clear();
+        self.register_map_table.extend(0..REGISTER_MAP_TABLE_SIZE);
+        self.halted = false;
+        self.idle_cycles = 0;
+        self.register_file_reads = 0;
+        self.cycle = 0;
+        self.next_retired_pc = None;
+        self.committed_instructions = 0;
+        self.instruction_mix.counts.clear();
+        self.stall_report = StallReport::default();
+        self.queue_waits.counts.clear();
+        self.peak_occupancy = PeakOccupancy::default();
+        self.trace.clear();
+    }
+
     pub fn is_done(&self) -> bool {
-        self.active_list.is_empty() && self.exception_mode == false
+        self.active_list.is_empty() && !self.exception_mode
+    }
+
+    /// Checks if no instruction is in flight: none is decoded, in the active list, in the integer
+    /// queue or in an ALU. Unlike `has_completed`, instructions may be left to fetch, e.g. from the
+    /// exception handler once a rollback is done.
+    pub fn is_fully_idle(&self) -> bool {
+        self.decoded_instructions.is_empty()
+            && self.active_list.is_empty()
+            && self.integer_queue.is_empty()
+            && self.alus.iter().all(|alu| alu.is_idle())
+    }
+
+    /// Checks if a halt instruction retired, ending the program.
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn active_list(&self) -> &[ActiveListEntry] {
+        &self.active_list
+    }
+
+    pub fn integer_queue(&self) -> &[IntegerQueueEntry] {
+        &self.integer_queue
+    }
+
+    /// Returns the PC of the instruction held by each slot of the integer queue, None for a free
+    /// slot.
+    pub fn integer_queue_slots(&self) -> Vec<Option<u64>> {
+        let mut slots = vec![None; self.config.integer_queue_size];
+        for entry in &self.integer_queue {
+            slots[entry.slot] = Some(entry.pc);
+        }
+        slots
+    }
+
+    pub fn free_list(&self) -> &VecDeque<u8> {
+        &self.free_list
+    }
+
+    /// Returns the number of entries held by the main structures, without serializing the state.
+    pub fn occupancy(&self) -> Occupancy {
+        Occupancy {
+            active_list: self.active_list.len(),
+            integer_queue: self.integer_queue.len(),
+            busy_alus: self.alus.iter().filter(|alu| alu.is_busy()).count(),
+            free_list: self.free_list.len(),
+        }
+    }
+
+    /// Lists the instructions fetched but not retired, oldest first: the ones in the active list,
+    /// then the ones waiting in the decoded buffer, with the stage each is in. Once a run is
+    /// stopped by the cycle cap, these are the instructions that never committed.
+    pub fn pending_report(&self) -> PendingReport {
+        let dispatched = self.active_list.iter().map(|entry| {
+            let status = if entry.is_exception {
+                PendingStatus::Exception
+            } else if self.exception_mode {
+                PendingStatus::Squashed
+            } else if entry.is_done {
+                PendingStatus::Done
+            } else if self.integer_queue.iter().any(|queued| queued.pc == entry.pc) {
+                PendingStatus::Queued
+            } else {
+                PendingStatus::Executing
+            };
+            (entry.pc, status)
+        });
+        let decoded = self.decoded_pcs.iter().map(|&pc| (pc, PendingStatus::Decoded));
+        PendingReport {
+            instructions: dispatched.chain(decoded).collect(),
+        }
+    }
+
+    pub fn physical_register_file(&self) -> &[u64] {
+        &self.physical_register_file
+    }
+
+    pub fn register_map_table(&self) -> &[u8] {
+        &self.register_map_table
+    }
+
+    pub fn busy_bit_table(&self) -> &[bool] {
+        &self.busy_bit_table
+    }
+
+    /// Instructions decoded in the last cycle, waiting to be renamed and dispatched.
+    pub fn decoded_instructions(&self) -> &[DecodedInstruction] {
+        &self.decoded_instructions
+    }
+
+    pub fn alus(&self) -> &[ALU] {
+        &self.alus
+    }
+
+    pub fn memory(&self) -> &[u64] {
+        &self.memory
+    }
+
+    /// Initializes the data memory from an image of 64-bit words, starting at address 0.
+    /// Words past the end of the image are cleared. Fails if the image exceeds the data memory.
+    pub fn load_memory(&mut self, words: &[u64]) -> Result<(), &'static str> {
+        if words.len() > self.memory.len() {
+            return Err("Memory image is larger than the data memory");
+        }
+        self.memory.fill(0);
+        self.memory[..words.len()].copy_from_slice(words);
+        Ok(())
+    }
+
+    /// Returns the value of every logical register, x0 first, read from the physical register it
+    /// is mapped to. Once the program completed, these are the committed architectural values.
+    pub fn architectural_registers(&self) -> Vec<u64> {
+        self.register_map_table
+            .iter()
+            .map(|&physical_register| self.physical_register_file[physical_register as usize])
+            .collect()
+    }
+
+    /// Writes an input value to a logical register before the program runs, in the physical
+    /// register it is mapped to. That register is committed, so the register map table and the
+    /// free list stay consistent. Fails if an instruction is in flight, or if the register is a
+    /// hardwired x0 and the value is not zero.
+    pub fn seed_register(&mut self, logical_register: u8, value: u64) -> Result<(), &'static str> {
+        if logical_register >= REGISTER_MAP_TABLE_SIZE {
+            return Err("Register index out of range");
+        }
+        if !self.is_fully_idle() {
+            return Err("Registers can only be seeded while no instruction is in flight");
+        }
+        if logical_register == 0 && self.config.hardwired_zero_register && value != 0 {
+            return Err("x0 is hardwired to zero");
+        }
+        let physical_register = self.map_register(logical_register);
+        self.physical_register_file[physical_register as usize] = value;
+        Ok(())
+    }
+
+    pub fn pc(&self) -> u64 {
+        self.pc
+    }
+
+    pub fn exception_pc(&self) -> u64 {
+        self.exception_pc
+    }
+
+    /// Returns the cause of the last exception raised, if any.
+    pub fn exception_cause(&self) -> Option<ExceptionCause> {
+        self.exception_cause
+    }
+
+    pub fn is_exception_mode(&self) -> bool {
+        self.exception_mode
+    }
+
+    /// Returns the number of instructions retired so far, excluding rolled back instructions.
+    pub fn committed_instructions(&self) -> u64 {
+        self.committed_instructions
+    }
+
+    /// Returns the number of cycles simulated since the initial state.
+    pub fn cycle(&self) -> u64 {
+        self.cycle
+    }
+
+    /// Returns the number of backpressure cycles caused by each exhausted resource.
+    pub fn stall_report(&self) -> StallReport {
+        self.stall_report
+    }
+
+    /// Returns the number of committed instructions of each op code.
+    pub fn instruction_mix(&self) -> &InstructionMix {
+        &self.instruction_mix
+    }
+
+    /// Returns the number of issued instructions by the cycles they waited in the integer queue.
+    pub fn queue_waits(&self) -> &QueueWaitHistogram {
+        &self.queue_waits
+    }
+
+    /// Returns the largest number of entries the active list and the integer queue held so far.
+    pub fn peak_occupancy(&self) -> PeakOccupancy {
+        self.peak_occupancy
+    }
+
+    /// Describes the instructions that moved through the pipeline during the last cycle.
+    pub fn trace_line(&self, cycle: usize) -> String {
+        format!("Cycle {}: {}", cycle, self.trace)
+    }
+
+    /// Describes the values forwarded from the ALUs to the integer queue during the last cycle.
+    pub fn forwarding_line(&self, cycle: usize) -> String {
+        format!("Cycle {} forwarding: {}", cycle, self.trace.forwarding_summary())
+    }
+
+    /// Returns the PC of every instruction retired during the last cycle, in retirement order.
+    pub fn retired_instructions(&self) -> &[u64] {
+        &self.trace.committed
+    }
+
+    /// Returns the results of the completed instructions waiting to retire, with the operation
+    /// each was produced from.
+    pub fn commit_buffer(&self) -> &[CommitBufferEntry] {
+        &self.commit_buffer
+    }
+
+    /// Returns the PC of every instruction rolled back during the last cycle, in rollback order.
+    pub fn rolled_back_instructions(&self) -> &[u64] {
+        &self.trace.rolled_back
+    }
+
+    /// Returns the PC of every instruction issued during the last cycle, with the index of the ALU
+    /// it was issued to.
+    pub fn issued_instructions(&self) -> &[(u64, usize)] {
+        &self.trace.issued
+    }
+
+    /// Returns the values forwarded from the ALUs to the integer queue during the last cycle, one
+    /// event per woken entry.
+    pub fn forwarding_events(&self) -> &[ForwardEvent] {
+        &self.trace.forwarded
     }
 
     /// Logs the current state of the processor to the state log.
@@ -82,268 +792,953 @@ impl Processor {
         *self = new_state.clone();
     }
 
+    /// Saves a checkpoint of the full processor state, configuration and counters included.
+    pub fn snapshot(&self) -> ProcessorSnapshot {
+        ProcessorSnapshot(self.clone())
+    }
+
+    /// Restores the processor to the state saved in the checkpoint.
+    pub fn restore(&mut self, snapshot: ProcessorSnapshot) {
+        *self = snapshot.0;
+    }
+
+    /// Checks if the program has run to completion: either a halt instruction retired, or every
+    /// instruction up to the end of the program was fetched, and every fetched instruction retired
+    /// or was rolled back.
+    pub fn has_completed(&self, instructions: &[Instruction]) -> bool {
+        self.is_done()
+            && (self.halted
+                || (self.decoded_instructions.is_empty()
+                    && self.instruction_index(self.pc, instructions).is_none()))
+    }
+
+    /// Returns the index in the program of the instruction at the given PC, if the PC lies within
+    /// the program loaded at the initial PC.
+    fn instruction_index(&self, pc: u64, instructions: &[Instruction]) -> Option<usize> {
+        let index = usize::try_from(pc.checked_sub(self.config.initial_pc)?).ok()?;
+        (index < instructions.len()).then_some(index)
+    }
+
+    /// Checks that every physical register is accounted for exactly once: it is either free,
+    /// mapped to a logical register, or the old destination of an in-flight instruction, to be
+    /// freed when it retires.
+    pub fn check_register_accounting(&self) -> Result<(), String> {
+        let in_flight_old_destinations = self
+            .active_list
+            .iter()
+            .filter(|entry| entry.has_destination)
+            .map(|entry| entry.old_destination);
+        let mut owners = vec![0; self.config.physical_register_file_size];
+        for register in self
+            .free_list
+            .iter()
+            .copied()
+            .chain(self.register_map_table.iter().copied())
+            .chain(in_flight_old_destinations)
+        {
+            owners[register as usize] += 1;
+        }
+        match owners.iter().position(|count| *count != 1) {
+            Some(register) => Err(format!(
+                "Physical register {} is accounted for {} times",
+                register, owners[register]
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Panics in debug builds if a physical register leaked or was freed twice, or if two
+    /// instructions occupy the same integer queue slot.
+    pub fn debug_assert_consistent(&self) {
+        if cfg!(debug_assertions) {
+            if let Err(e) = self.check_register_accounting() {
+                panic!("Inconsistent register accounting: {}", e);
+            }
+            let mut occupied = vec![false; self.config.integer_queue_size];
+            for entry in &self.integer_queue {
+                assert!(!occupied[entry.slot], "Integer queue slot {} reused", entry.slot);
+                occupied[entry.slot] = true;
+            }
+        }
+    }
+
+    /// Panics in debug builds if a register taken from the free list is still in use: it must not
+    /// be busy, left in the free list, mapped, the destination of a queued instruction, or the old
+    /// destination an in-flight instruction frees when it retires. Writing such a register would
+    /// corrupt the value another instruction reads.
+    fn debug_assert_unused(&self, register: u8) {
+        if cfg!(debug_assertions) {
+            let uses = [
+                (self.busy_bit_table[register as usize], "busy"),
+                (self.free_list.contains(&register), "still in the free list"),
+                (self.register_map_table.contains(&register), "mapped to a logical register"),
+                (
+                    self.integer_queue
+                        .iter()
+                        .any(|entry| entry.writes_register && entry.dest_register == register),
+                    "the destination of a queued instruction",
+                ),
+                (
+                    self.active_list
+                        .iter()
+                        .any(|entry| entry.has_destination && entry.old_destination == register),
+                    "the old destination of an in-flight instruction",
+                ),
+            ];
+            for (is_used, use_description) in uses {
+                assert!(!is_used, "p{} allocated while {}", register, use_description);
+            }
+        }
+    }
+
+    /// Panics in debug builds if an instruction about to issue reads an operand that is not ready:
+    /// every source register must have been written back, or be forwarded this cycle.
+    fn debug_assert_hazard_free(&self, entry: &IntegerQueueEntry) {
+        if cfg!(debug_assertions) {
+            assert!(entry.is_ready(), "PC {} issued with an operand not ready", entry.pc);
+            for &register in &entry.source_registers {
+                // Results raising an exception are never forwarded
+                let is_forwarded = self.alus.iter().any(|alu| {
+                    (alu.is_forwarding
+                        && alu.forwarding_writes_register
+                        && alu.forwarding_exception.is_none()
+                        && alu.forwarding_reg == register)
+                        || alu.forwarding_fused.as_ref().is_some_and(|fused| {
+                            fused.exception.is_none() && fused.reg == register
+                        })
+                });
+                assert!(
+                    self.register_is_ready(register) || is_forwarded,
+                    "PC {} issued while its source register p{} is still busy",
+                    entry.pc,
+                    register
+                );
+            }
+        }
+    }
+
+    /// Advances the processor by one cycle in place, propagating and latching the next state.
+    /// Does nothing once the program has completed. Returns whether the program is still running.
+    pub fn step(&mut self, instructions: &[Instruction]) -> Result<bool, String> {
+        if self.has_completed(instructions) {
+            return Ok(false);
+        }
+        let new_state = self.propagate(instructions)?;
+        self.latch(&new_state);
+        Ok(!self.has_completed(instructions))
+    }
+
+    /// Advances the processor by up to `cycles` cycles in place, stopping early once the program
+    /// has completed. Returns the number of cycles actually run.
+    pub fn propagate_n(
+        &mut self,
+        instructions: &[Instruction],
+        cycles: usize,
+    ) -> Result<usize, String> {
+        let mut executed = 0;
+        while executed < cycles && !self.has_completed(instructions) {
+            self.step(instructions)?;
+            executed += 1;
+        }
+        Ok(executed)
+    }
+
     /// Propagates the processor state by one cycle.
-    pub fn propagate(&self, instructions: &mut Vec<Instruction>) -> Processor {
+    /// Instructions are fetched from `instructions` at the index given by the PC.
+    /// Fails if a fetched instruction cannot be decoded, or if the processor is deadlocked.
+    pub fn propagate(&self, instructions: &[Instruction]) -> Result<Processor, String> {
         let mut next_state = self.clone();
+        next_state.trace.clear();
+        next_state.cycle += 1;
         let mut backpressure = false;
-        next_state.commit();
-        if !next_state.exception_mode {
+        let squashed = next_state.commit()?;
+        // A taken branch squashed the instructions decoded in the previous cycle
+        if !next_state.exception_mode && !squashed {
             next_state.issue();
-            backpressure = next_state.rename_and_dispatch(&self);
+            backpressure = next_state.rename_and_dispatch(self);
         }
-        next_state.fetch_and_decode(instructions, backpressure);
-        return next_state;
+        next_state.fetch_and_decode(instructions, backpressure)?;
+        next_state.debug_assert_consistent();
+        next_state.check_progress()?;
+        next_state.check_instruction_ages()?;
+        Ok(next_state)
+    }
+
+    /// Ages every instruction of the active list by one cycle. Fails once an instruction exceeded
+    /// `instruction_cycle_budget` cycles without retiring, listing every such instruction, oldest
+    /// first, with the reason it is stuck.
+    fn check_instruction_ages(&mut self) -> Result<(), String> {
+        for entry in self.active_list.iter_mut() {
+            entry.age += 1;
+        }
+        let budget = self.config.instruction_cycle_budget;
+        let stuck_instructions: Vec<String> = self
+            .active_list
+            .iter()
+            .filter(|entry| entry.age > budget)
+            .map(|entry| format!("PC {} ({})", entry.pc, self.stall_reason(entry)))
+            .collect();
+        if stuck_instructions.is_empty() {
+            return Ok(());
+        }
+        Err(format!(
+            "Instruction not retired after {} cycles: {}",
+            budget,
+            stuck_instructions.join(", ")
+        ))
+    }
+
+    /// Describes why an instruction of the active list has not retired yet.
+    fn stall_reason(&self, entry: &ActiveListEntry) -> String {
+        if entry.is_exception {
+            return "raised an exception, waiting for the rollback".to_string();
+        }
+        if entry.is_done {
+            return "done, waiting for older instructions to retire".to_string();
+        }
+        if let Some(alu_index) = self.alus.iter().position(|alu| alu.holds(entry.pc)) {
+            return format!("executing in ALU {}", alu_index);
+        }
+        match self.integer_queue.iter().find(|x| x.pc == entry.pc) {
+            Some(queued) if !queued.op_a_is_ready => {
+                format!("operand A never became ready, waiting on p{}", queued.op_a_reg_tag)
+            }
+            Some(queued) if !queued.op_b_is_ready => {
+                format!("operand B never became ready, waiting on p{}", queued.op_b_reg_tag)
+            }
+            Some(_) => "ready, but no ALU was free to issue it".to_string(),
+            None => "not found in the integer queue nor in an ALU".to_string(),
+        }
+    }
+
+    /// Counts the consecutive cycles in which no instruction moved through the pipeline.
+    /// Fails once `deadlock_cycles` of them passed, describing where the processor is stuck.
+    fn check_progress(&mut self) -> Result<(), String> {
+        if !self.trace.is_idle() {
+            self.idle_cycles = 0;
+            return Ok(());
+        }
+        self.idle_cycles += 1;
+        if self.idle_cycles < self.config.deadlock_cycles {
+            return Ok(());
+        }
+        let oldest_pc = match self.active_list.first() {
+            Some(entry) => entry.pc.to_string(),
+            None => "none".to_string(),
+        };
+        Err(format!(
+            "Deadlock: no progress for {} cycles at PC {}, oldest instruction in flight: {}, \
+             {} instructions in the active list, {} in the integer queue",
+            self.idle_cycles,
+            self.pc,
+            oldest_pc,
+            self.active_list.len(),
+            self.integer_queue.len()
+        ))
     }
 
     /// STAGE 1: Fetches and decodes the next four instructions from the instruction queue.
     /// 1. If backpressure is applied or an exception occurs, the fetch and decode process is halted,
-    /// the PC is set to the exception PC, and the decoded instructions are cleared.
+    ///    the PC is set to the exception PC, and the decoded instructions are cleared.
     /// 2. If the instruction queue is empty, the process is also halted.
-    /// 3. Otherwise, the next up to four instructions are fetched and decoded.
-    fn fetch_and_decode(&mut self, instructions: &mut Vec<Instruction>, backpressure: bool) {
-        if backpressure {
-            return; // Do not fetch and decode
+    /// 3. Otherwise, the next instructions are fetched and decoded, up to `fetch_width` of them
+    ///    and until the decoded buffer is full.
+    /// 4. An instruction that cannot be decoded is reported with its text and index.
+    /// 5. Fetching stops after a halt instruction until it is rolled back, and for good once it
+    ///    retires.
+    fn fetch_and_decode(
+        &mut self,
+        instructions: &[Instruction],
+        backpressure: bool,
+    ) -> Result<(), String> {
+        if backpressure || self.halted {
+            return Ok(()); // Do not fetch and decode
         }
         if self.exception_mode {
-            self.pc = EXCEPTION_PC;
+            self.pc = self.config.exception_handler_pc;
             self.clear_decoded_instructions();
-            return; // Do not fetch and decode and clear decoded instructions
-        }
-        while self.decoded_instructions.len() < DECODED_BUFFER_SIZE && !instructions.is_empty() {
-            if let Some(instruction) = instructions.pop() {
-                self.decoded_pcs.push(self.pc);
-                let decoded_instruction = instruction.decode(self.pc).expect("Invalid instruction");
-                self.decoded_instructions.push(decoded_instruction);
-                self.pc += 1;
+            return Ok(()); // Do not fetch and decode and clear decoded instructions
+        }
+        if self.has_pending_halt() {
+            return Ok(()); // Nothing past a halt is fetched unless it is rolled back
+        }
+        let mut fetched = 0;
+        while self.decoded_instructions.len() < self.config.decoded_buffer_size
+            && fetched < self.config.fetch_width
+        {
+            let Some(index) = self.instruction_index(self.pc, instructions) else {
+                break; // The PC is outside the program
+            };
+            let instruction = &instructions[index];
+            let decoded_instruction = instruction.decode(self.pc).map_err(|error| {
+                format!("{} at index {}: {}", error, index, instruction.text())
+            })?;
+            if instruction.is_load_immediate() && !self.config.hardwired_zero_register {
+                return Err(format!(
+                    "li needs the hardwired zero register at index {}: {}",
+                    index,
+                    instruction.text()
+                ));
+            }
+            self.decoded_pcs.push(self.pc);
+            self.trace.decoded.push(self.pc);
+            let is_halt = decoded_instruction.is_halt();
+            self.decoded_instructions.push(decoded_instruction);
+            self.pc = self
+                .pc
+                .checked_add(1)
+                .ok_or_else(|| format!("PC overflow after the instruction at PC {}", self.pc))?;
+            fetched += 1;
+            if is_halt {
+                break;
             }
         }
+        Ok(())
+    }
+
+    /// Checks if a halt instruction was fetched and has not retired yet.
+    fn has_pending_halt(&self) -> bool {
+        self.decoded_instructions.iter().any(|i| i.is_halt())
+            || self.active_list.iter().any(|entry| entry.is_halt)
     }
 
     /// STAGE 2: Performs the rename and dispatch process for the decoded instructions.
     /// 1. Checks if there are enough resources to process the next four instructions.
     /// 2. If there are enough resources, renames the destination registers and dispatches the
-    /// instructions to the integer queue and active list as per the R10000 CPU paper.
+    ///    instructions to the integer queue and active list as per the R10000 CPU paper.
     /// 3. If there are not enough resources, backpressure is applied.
     /// 4. The integer queue is always listening for forwarding paths from the ALUs.
+    ///
+    /// Only the decoded instructions are taken from `current_state`, the state latched in the
+    /// previous cycle. Renaming and operand capture read the state being built instead: commit
+    /// already wrote back this cycle's ALU results and cleared their busy bits, and issue already
+    /// forwarded them to the entries waiting in the integer queue. An operand produced this cycle
+    /// is therefore captured as ready with its value, since no forwarding path will broadcast it
+    /// again.
     fn rename_and_dispatch(&mut self, current_state: &Processor) -> bool {
         if !self.has_sufficient_resources() {
             return true; // Apply backpressure if resources are insufficient.
         }
-        for decoded_instruction in &current_state.decoded_instructions {
-            self.add_active_list_entry(decoded_instruction);
-            self.add_integer_queue_entry(decoded_instruction);
+        self.register_file_reads = 0;
+        for (dispatched, decoded_instruction) in
+            current_state.decoded_instructions.iter().enumerate()
+        {
+            // The destination's previous mapping is read before the integer queue entry renames it
+            let old_dest_register = self.map_register(decoded_instruction.logical_destination);
+            // A nop or halt is done on dispatch, it only waits in the active list to retire in order
+            let stall = if decoded_instruction.needs_alu() {
+                self.add_integer_queue_entry(decoded_instruction).err()
+            } else {
+                None
+            };
+            if let Some(stall) = stall {
+                // The rest of the decoded buffer waits for the next cycle
+                match stall {
+                    DispatchStall::FreeList => self.stall_report.free_list += 1,
+                    DispatchStall::ReadPorts => self.stall_report.read_ports += 1,
+                }
+                self.decoded_instructions.drain(..dispatched);
+                self.decoded_pcs.drain(..dispatched);
+                return true;
+            }
+            self.add_active_list_entry(decoded_instruction, old_dest_register);
+            self.trace.dispatched.push(decoded_instruction.pc);
         }
         self.clear_decoded_instructions();
         false // No backpressure since instructions were successfully renamed and dispatched.
     }
 
-    /// STAGE 3: Performs the issue process for the decoded instructions.
-    /// 1. Checks if the instruction is ready to be issued, prioritizing the oldest instructions,
-    /// (i.e., the instructions with smaller PCs).
-    /// 2. If ready, issues the instruction to an available ALU.
-    /// 3. The integer queue is always listening for forwarding paths from the ALUs.
+    /// STAGE 3: Performs the issue process for the decoded instructions, in this order:
+    /// 1. Forward: the results the ALUs produced in the previous cycle are read from their
+    ///    forwarding paths.
+    /// 2. Wake: the integer queue captures the forwarded values, so an entry waiting on them
+    ///    becomes ready in this very cycle.
+    /// 3. Select: the ALUs advance by one stage, then every free ALU picks the oldest ready
+    ///    instruction it can execute (i.e., the instruction with the smallest PC).
+    /// 4. Issue: the selected instructions leave the integer queue for their ALU.
+    ///
+    /// Waking comes before the ALUs advance, since advancing replaces the results on the
+    /// forwarding paths with the ones of this cycle, which are forwarded in the next cycle.
+    ///
+    /// The assignment of instructions to ALUs is deterministic: free ALUs are filled in ascending
+    /// index order, each receiving the oldest ready instruction left that it can execute. Unless
+    /// divisions are restricted to some ALUs, the oldest ready instruction of a cycle therefore
+    /// always goes to the free ALU with the lowest index. At most `issue_width` ALUs are filled per
+    /// cycle.
     fn issue(&mut self) {
-        self.read_integer_queue_fwd_paths();
+        for result in self.read_integer_queue_fwd_paths() {
+            self.forward_to_integer_queue(result);
+        }
         for alu in self.alus.iter_mut() {
             alu.execute();
         }
-        for _ in 0..ALU_COUNT {
-            self.issue_instruction();
+        self.select_and_issue();
+    }
+
+    /// Fills the free ALUs, in ascending index order, with the oldest ready instruction each can
+    /// execute, filling at most `issue_width` ALUs.
+    fn select_and_issue(&mut self) {
+        let mut issued_instructions = 0;
+        for alu_index in 0..self.alus.len() {
+            if issued_instructions == self.config.issue_width {
+                break; // The issue ports are all taken
+            }
+            if self.alus[alu_index].is_busy() {
+                continue;
+            }
+            if self.issue_instruction(alu_index) {
+                issued_instructions += 1;
+            }
+            // Otherwise nothing this ALU can execute is ready, a divider may still be free
         }
     }
 
     /// STAGE 4: Commits the results of the executed instructions to the physical register file.
     /// 1. Mark instructions as done or exception on receiving the results from the ALU
-    /// forwarding paths.
+    ///    forwarding paths.
     /// 2. Respectively, retire or rollback the instructions in the active list depending on the
-    /// results.
+    ///    results.
     /// 3. Recycle the physical registers of the retired instructions, pushing them back to the
-    /// free list.
-    fn commit(&mut self) {
+    ///    free list.
+    /// 4. When a taken branch retires, squash every younger instruction and redirect the PC to the
+    ///    branch target. When a halt retires, flush the pipeline and stop the processor.
+    ///    Returns whether the pipeline was squashed.
+    ///
+    /// Fails if a forwarded result belongs to no instruction of the active list, or if its value
+    /// cannot be found in the commit buffer.
+    fn commit(&mut self) -> Result<bool, String> {
         if self.exception_mode {
             if self.active_list.is_empty() {
                 self.exception_mode = false;
             }
-            return self.rollback();
+            self.rollback();
+            return Ok(false);
         }
 
         let mut retired_instructions = 0;
-        let mut to_remove_pcs: Vec<u64> = Vec::new();
+        let mut redirect_pc = None;
+        let mut halt = false;
 
-        for entry in self.clone().active_list.iter() {
-            if retired_instructions == DECODED_BUFFER_SIZE {
-                break; // Stop committing if four instructions are already picked.
-            }
+        // Entries retire in order, so the retired instructions form a prefix of the active list.
+        while retired_instructions < self.config.commit_width {
+            let Some(entry) = self.active_list.get(retired_instructions) else {
+                break;
+            };
             if entry.is_exception {
-                self.set_exception_mode(entry.pc);
+                let (exception_pc, cause) = (entry.pc, entry.exception_cause);
+                self.set_exception_mode(exception_pc, cause);
                 break;
             } else if entry.is_done {
+                let (pc, has_destination, old_destination, is_halt) =
+                    (entry.pc, entry.has_destination, entry.old_destination, entry.is_halt);
+                if !self.retire_memory_access(pc, has_destination) {
+                    // Invalid address, raised precisely at commit
+                    self.set_exception_mode(pc, Some(ExceptionCause::InvalidAddress));
+                    break;
+                }
                 retired_instructions += 1;
-                self.free_list.push(entry.old_destination);
-                to_remove_pcs.push(entry.pc);
+                self.committed_instructions += 1;
+                if has_destination {
+                    self.free_list.push_back(old_destination);
+                }
+                redirect_pc = self
+                    .commit_buffer
+                    .iter()
+                    .find(|x| x.pc == pc)
+                    .and_then(|x| x.redirect_pc);
+                if redirect_pc.is_some() {
+                    break; // Younger instructions are on the wrong path.
+                }
+                if is_halt {
+                    halt = true;
+                    break; // Younger instructions are past the end of the program.
+                }
             } else {
                 break; // Stop committing if an instruction is not completed yet.
             }
         }
 
-        for pc in to_remove_pcs {
-            self.active_list.retain(|x| x.pc != pc);
-            self.commit_buffer.retain(|x| x.pc != pc);
+        for entry in self.active_list.drain(..retired_instructions).collect::<Vec<_>>() {
+            self.debug_assert_retirement_order(entry.pc);
+            self.commit_buffer.retain(|x| x.pc != entry.pc);
+            self.instruction_mix.record(&entry.op_code);
+            self.trace.committed.push(entry.pc);
+        }
+
+        if let Some(target) = redirect_pc {
+            self.squash(target);
+            return Ok(true);
+        }
+        if halt {
+            self.flush_pipeline();
+            self.halted = true;
+            return Ok(true);
+        }
+        self.read_active_list_fwd_paths()?;
+        Ok(false)
+    }
+
+    /// Panics in debug builds if an instruction retires out of program order: every retired PC
+    /// follows the previous one, unless a taken branch or an exception redirected fetch.
+    fn debug_assert_retirement_order(&mut self, pc: u64) {
+        if cfg!(debug_assertions) {
+            if let Some(expected_pc) = self.next_retired_pc {
+                assert_eq!(pc, expected_pc, "PC {} retired out of program order", pc);
+            }
+            self.next_retired_pc = Some(pc + 1);
         }
-        self.read_active_list_fwd_paths();
     }
 
     /// EXCEPTION MODE: Rollback instructions and recover register map table, busy bit table,
     /// and free list.
+    /// Entries are undone youngest first, as in the R10000: each restores the mapping its
+    /// instruction replaced, so the oldest one restores the mapping from before the exception.
     fn rollback(&mut self) {
-        let mut rolled_back_instructions = 0;
-        let mut to_remove_pcs: Vec<u64> = Vec::new();
+        // Stop rolling back once `commit_width` instructions are picked, youngest first.
+        let rolled_back_instructions = if self.config.instant_rollback {
+            self.active_list.len()
+        } else {
+            self.config.commit_width.min(self.active_list.len())
+        };
+        self.roll_back_youngest(rolled_back_instructions);
+    }
+
+    /// BRANCH RESOLUTION: Squash every instruction younger than a retired taken branch and
+    /// continue fetching from its target. Since the branch was the oldest instruction, everything
+    /// left in the pipeline was fetched on the wrong path.
+    fn squash(&mut self, target: u64) {
+        self.flush_pipeline();
+        self.next_retired_pc = Some(target);
+        self.pc = target;
+        self.trace.redirect = Some(target);
+    }
+
+    /// Discards every instruction in flight once the oldest one retired: the active list is rolled
+    /// back at once, and the integer queue, ALUs and decoded instructions are cleared.
+    fn flush_pipeline(&mut self) {
+        self.roll_back_youngest(self.active_list.len());
+        self.reset_alus();
+        self.reset_integer_queue();
+        self.clear_decoded_instructions();
+        self.commit_buffer.clear();
+    }
+
+    /// Rolls back the `count` youngest active list entries, youngest first, recovering the
+    /// register map table, busy bit table, and free list.
+    fn roll_back_youngest(&mut self, count: usize) {
+        let first_rolled_back = self.active_list.len() - count;
 
-        for entry in self.clone().active_list.iter().rev() {
-            if rolled_back_instructions == DECODED_BUFFER_SIZE {
-                break; // Stop rolling back if four instructions are already picked.
+        for index in (first_rolled_back..self.active_list.len()).rev() {
+            let logical_destination = self.active_list[index].logical_destination;
+            let old_destination = self.active_list[index].old_destination;
+            if !self.active_list[index].has_destination {
+                continue; // No physical register was allocated
             }
-            rolled_back_instructions += 1;
-            let allocated_register = self.map_register(entry.logical_destination);
+            let allocated_register = self.map_register(logical_destination);
             self.set_free(allocated_register);
-            self.free_list.push(allocated_register);
-            self.register_map_table[entry.logical_destination as usize] = entry.old_destination;
-            to_remove_pcs.push(entry.pc);
+            self.free_list.push_back(allocated_register);
+            self.register_map_table[logical_destination as usize] = old_destination;
         }
 
-        for pc in to_remove_pcs {
-            self.active_list.retain(|x| x.pc != pc);
-            self.commit_buffer.retain(|x| x.pc != pc);
+        for entry in self.active_list.drain(first_rolled_back..).rev() {
+            self.commit_buffer.retain(|x| x.pc != entry.pc);
+            self.trace.rolled_back.push(entry.pc);
         }
     }
 
-    /// =============================================== ///
-    /// --------------- Helper Functions -------------- ///
-    /// =============================================== ///
+    // =============================================== //
+    // --------------- Helper Functions -------------- //
+    // =============================================== //
 
     /// Clear active list entry and update register with new value
-    pub fn commit_entry(&mut self, entry: ActiveListEntry) {
-        let buffer_entry = self
-            .commit_buffer
-            .iter()
-            .find(|x| x.pc == entry.pc)
-            .unwrap();
-        self.physical_register_file[buffer_entry.dest_register as usize] = buffer_entry.value;
-        self.set_free(buffer_entry.dest_register);
+    /// Fails if the commit buffer holds no result for the instruction of the entry.
+    pub fn commit_entry(&mut self, entry: ActiveListEntry) -> Result<(), String> {
+        let Some(buffer_entry) = self.commit_buffer.iter().find(|x| x.pc == entry.pc) else {
+            return Err(format!("No commit buffer entry for the instruction at PC {}", entry.pc));
+        };
+        let dest_register = buffer_entry.dest_register;
+        if entry.has_destination {
+            self.physical_register_file[dest_register as usize] = buffer_entry.value;
+        }
+        self.set_free(dest_register);
+        Ok(())
+    }
+
+    /// Performs the memory access of a retiring load or store, if any. Loads write the loaded
+    /// value to their destination register and forward it to the integer queue, unless the write
+    /// is discarded, as for a load into a hardwired x0.
+    /// Returns false if the address is misaligned or out of bounds.
+    fn retire_memory_access(&mut self, pc: u64, has_destination: bool) -> bool {
+        let Some(buffer_entry) = self.commit_buffer.iter().find(|x| x.pc == pc) else {
+            return true;
+        };
+        let dest_register = buffer_entry.dest_register;
+        match buffer_entry.memory_access {
+            None => true,
+            Some(MemoryAccess::Load { address }) => match self.memory_index(address) {
+                Some(index) => {
+                    if has_destination {
+                        let value = self.memory[index];
+                        self.physical_register_file[dest_register as usize] = value;
+                        self.set_free(dest_register);
+                        self.update_integer_queue(dest_register, value);
+                    }
+                    true
+                }
+                None => false,
+            },
+            Some(MemoryAccess::Store { address, value }) => match self.memory_index(address) {
+                Some(index) => {
+                    self.memory[index] = value;
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    /// Returns the index of the memory word at the given byte address, if it is aligned and in
+    /// bounds.
+    fn memory_index(&self, address: u64) -> Option<usize> {
+        if !address.is_multiple_of(MEMORY_WORD_SIZE) {
+            return None;
+        }
+        let index = usize::try_from(address / MEMORY_WORD_SIZE).ok()?;
+        (index < self.memory.len()).then_some(index)
     }
 
     /// Sets exception mode
-    pub fn set_exception_mode(&mut self, pc: u64) {
+    pub fn set_exception_mode(&mut self, pc: u64, cause: Option<ExceptionCause>) {
         self.exception_mode = true;
+        self.exception_cause = cause;
+        self.next_retired_pc = None; // Fetching continues from the exception handler
         self.exception_pc = pc;
-        self.reset_alus();
-        self.reset_integer_queue();
+        self.trace.exception = Some(pc);
+        self.squash_younger_than(pc);
     }
 
-    /// Issues the oldest ready instruction to an available ALU.
-    fn issue_instruction(&mut self) {
-        let oldest_ready_instruction = self.find_oldest_ready_instruction();
-        if let Some(entry) = oldest_ready_instruction {
-            for alu in self.alus.iter_mut() {
-                if !alu.is_busy() {
-                    alu.latch(entry.clone());
-                    break;
-                }
+    /// Removes every instruction younger than `pc` from the decoded buffer, the integer queue and
+    /// the ALUs, along with their results held back by the forwarding bubble. Older instructions
+    /// are left untouched, as is the active list, recovered by the rollback.
+    ///
+    /// Age ordering: in-flight instructions follow each other in program order from the oldest,
+    /// since fetch is only redirected once a branch retires, so a larger PC is younger.
+    pub fn squash_younger_than(&mut self, pc: u64) {
+        self.decoded_pcs.retain(|&decoded_pc| decoded_pc <= pc);
+        self.decoded_instructions.retain(|instruction| instruction.pc <= pc);
+        self.integer_queue.retain(|entry| entry.pc <= pc);
+        self.compact_integer_queue();
+        for alu in self.alus.iter_mut() {
+            alu.squash_younger_than(pc);
+        }
+        self.delayed_forwards.retain(|forward| forward.producer_pc <= pc);
+    }
+
+    /// Issues the oldest ready instruction the given free ALU can execute to it.
+    /// Returns false, leaving the ALU idle, if no such instruction is ready.
+    fn issue_instruction(&mut self, alu_index: usize) -> bool {
+        let Some(entry) = self.find_oldest_ready_instruction(alu_index) else {
+            return false;
+        };
+        self.debug_assert_hazard_free(&entry);
+        self.trace.issued.push((entry.pc, alu_index));
+        self.queue_waits.record(self.cycle - entry.dispatch_cycle);
+        let latency = self.config.latency(&entry.op_code);
+        match self.take_fused_partner(&entry) {
+            Some(partner) => {
+                self.debug_assert_hazard_free(&partner);
+                self.trace.issued.push((partner.pc, alu_index));
+                self.queue_waits.record(self.cycle - partner.dispatch_cycle);
+                self.alus[alu_index].latch_fused(entry, partner, latency);
             }
+            None => self.alus[alu_index].latch(entry, latency),
+        }
+        true
+    }
+
+    /// When divmod fusion is enabled, removes from the integer queue the ready partner of an issued
+    /// `divu` or `remu`: the instruction next to it computing the other half of the division from
+    /// the same source registers.
+    fn take_fused_partner(&mut self, entry: &IntegerQueueEntry) -> Option<IntegerQueueEntry> {
+        if !self.config.fuse_divmod || entry.is_immediate {
+            return None;
         }
+        let partner_op_code = match entry.op_code.as_str() {
+            "divu" => "remu",
+            "remu" => "divu",
+            _ => return None,
+        };
+        let index = self.integer_queue.iter().position(|candidate| {
+            (candidate.pc + 1 == entry.pc || candidate.pc == entry.pc + 1)
+                && candidate.op_code == partner_op_code
+                && !candidate.is_immediate
+                && candidate.is_ready()
+                && candidate.source_registers == entry.source_registers
+                && (candidate.op_a_value, candidate.op_b_value)
+                    == (entry.op_a_value, entry.op_b_value)
+        })?;
+        Some(self.remove_integer_queue_entry(index))
     }
 
-    /// Finds the oldest instruction in the integer queue that is ready to be issued.
-    fn find_oldest_ready_instruction(&mut self) -> Option<IntegerQueueEntry> {
-        let mut sorted_queue = self.integer_queue.clone();
-        sorted_queue.sort_by(|a, b| a.pc.cmp(&b.pc));
+    /// Finds the oldest instruction in the integer queue that is ready to be issued, removing it
+    /// from the queue. The queue is kept sorted by PC, so the first ready entry is the oldest.
+    ///
+    /// Age ordering: a smaller PC is older. Branches and jumps only redirect fetch once they
+    /// retire, squashing everything younger, so the instructions in flight always follow program
+    /// order. Entries sharing a PC keep their dispatch order, the earliest dispatched issuing first.
+    /// Only instructions the given ALU can execute are considered.
+    fn find_oldest_ready_instruction(&mut self, alu_index: usize) -> Option<IntegerQueueEntry> {
+        let index = self.integer_queue.iter().position(|entry| {
+            entry.is_ready() && self.config.can_execute(alu_index, &entry.op_code)
+        })?;
+        Some(self.remove_integer_queue_entry(index))
+    }
 
-        for entry in sorted_queue {
-            if entry.is_ready() {
-                self.integer_queue.retain(|x| x.pc != entry.pc);
-                return Some(entry);
+    /// Removes the entry at the given index of the integer queue, freeing its slot.
+    fn remove_integer_queue_entry(&mut self, index: usize) -> IntegerQueueEntry {
+        let entry = self.integer_queue.remove(index);
+        self.compact_integer_queue();
+        entry
+    }
+
+    /// With a compacting integer queue, shifts the entries down to the lowest slots, oldest first.
+    /// The queue is sorted by age, so each entry moves to the slot of its index.
+    fn compact_integer_queue(&mut self) {
+        if self.config.compacting_integer_queue {
+            for (slot, entry) in self.integer_queue.iter_mut().enumerate() {
+                entry.slot = slot;
             }
         }
-        None
+    }
+
+    /// Returns the slot a newly dispatched instruction is allocated: the one after the youngest
+    /// entry with a compacting queue, the lowest free one otherwise.
+    fn allocate_integer_queue_slot(&self) -> usize {
+        if self.config.compacting_integer_queue {
+            return self.integer_queue.len();
+        }
+        (0..self.config.integer_queue_size)
+            .find(|&slot| self.integer_queue.iter().all(|entry| entry.slot != slot))
+            .expect("dispatch checks the integer queue has a free slot")
+    }
+
+    /// Returns the number of slots of the integer queue no instruction occupies.
+    fn free_integer_queue_slots(&self) -> usize {
+        self.config.integer_queue_size - self.integer_queue.len()
     }
 
     /// The active list is polled for the forwarding paths from the ALUs to check if any values have
     /// been forwarded. If so, the active list updates the relevant entries with the forwarded values.
     /// The active list is also updated with the exception status of the forwarded values.
-    fn read_active_list_fwd_paths(&mut self) {
-        for alu in self.alus.clone().iter() {
-            if alu.is_forwarding {
-                self.update_active_list(alu);
+    fn read_active_list_fwd_paths(&mut self) -> Result<(), String> {
+        for alu_index in 0..self.alus.len() {
+            if self.alus[alu_index].is_forwarding {
+                self.update_active_list(alu_index)?;
             }
         }
+        Ok(())
     }
 
     /// The active list checks if any of its entries are ready to be issued,
     /// and if so, updates the entries accordingly.
-    fn update_active_list(&mut self, alu: &ALU) {
+    fn update_active_list(&mut self, alu_index: usize) -> Result<(), String> {
+        let alu = &self.alus[alu_index];
+        let result = CommitBufferEntry::new(
+            alu.forwarding_reg,
+            alu.forwarding_value,
+            alu.forwarding_pc,
+            alu.forwarding_memory_access,
+            alu.forwarding_redirect_pc,
+            alu.forwarding_op_code.clone(),
+            alu.forwarding_op_a_value,
+            alu.forwarding_op_b_value,
+        );
+        let (exception, writes_register) = (alu.forwarding_exception, alu.forwarding_writes_register);
+        let fused = alu.forwarding_fused.clone();
+        self.complete_active_list_entry(result, exception, writes_register)?;
+        if let Some(fused) = fused {
+            // The fused partner was computed from the same operands
+            let (op_a_value, op_b_value) = (
+                self.alus[alu_index].forwarding_op_a_value,
+                self.alus[alu_index].forwarding_op_b_value,
+            );
+            let result = CommitBufferEntry::new(
+                fused.reg,
+                fused.value,
+                fused.pc,
+                None,
+                None,
+                fused.op_code,
+                op_a_value,
+                op_b_value,
+            );
+            self.complete_active_list_entry(result, fused.exception, true)?;
+        }
+        Ok(())
+    }
+
+    /// Marks the active list entry of a forwarded result as done, or as raising the exception.
+    /// The result is buffered until the entry retires, and its register value written back.
+    /// Fails if no instruction of the active list produced the result.
+    fn complete_active_list_entry(
+        &mut self,
+        result: CommitBufferEntry,
+        exception: Option<ExceptionCause>,
+        writes_register: bool,
+    ) -> Result<(), String> {
         let mut to_commit_entries: Vec<ActiveListEntry> = Vec::new();
+        let mut found = false;
         for entry in self.active_list.iter_mut() {
-            if entry.pc == alu.forwarding_pc {
+            if entry.pc == result.pc {
+                found = true;
                 entry.is_done = true;
-                if alu.forwarding_exception {
+                if exception.is_some() {
                     entry.is_exception = true;
+                    entry.exception_cause = exception;
                 } else {
-                    to_commit_entries.push(entry.clone());
-                    self.commit_buffer.push(CommitBufferEntry::new(
-                        alu.forwarding_reg,
-                        alu.forwarding_value,
-                        entry.pc,
-                    ));
+                    self.commit_buffer.push(result.clone());
+                    // Loads, stores and branches have no register value to write back yet
+                    if writes_register {
+                        to_commit_entries.push(entry.clone());
+                    }
                 }
             }
         }
+        if !found {
+            return Err(format!("No active list entry for the result of PC {}", result.pc));
+        }
         for entry in to_commit_entries {
-            self.commit_entry(entry);
+            self.commit_entry(entry)?;
         }
+        Ok(())
     }
 
-    /// The integer queue polls the forwarding paths from the ALUs to check if any values have been
-    /// forwarded. If so, the integer queue updates the relevant entries with the forwarded values.
-    fn read_integer_queue_fwd_paths(&mut self) {
-        for alu in self.alus.clone().iter() {
-            if alu.is_forwarding {
-                self.update_integer_queue(alu.forwarding_reg, alu.forwarding_value, alu.forwarding_exception);
+    /// The integer queue polls the forwarding paths from the ALUs, returning the results forwarded
+    /// to it. A result raising an exception has no meaningful value: only the active list sees it,
+    /// and its consumers keep waiting until they are rolled back.
+    /// With the forwarding bubble, the results are held for a cycle and the ones read in the
+    /// previous cycle are returned instead.
+    fn read_integer_queue_fwd_paths(&mut self) -> Vec<ForwardedResult> {
+        let mut results = Vec::new();
+        for (alu_index, alu) in self.alus.iter().enumerate() {
+            if alu.is_forwarding
+                && alu.forwarding_writes_register
+                && alu.forwarding_exception.is_none()
+            {
+                results.push(ForwardedResult {
+                    alu_index,
+                    register: alu.forwarding_reg,
+                    value: alu.forwarding_value,
+                    producer_pc: alu.forwarding_pc,
+                });
             }
+            if let Some(fused) = alu
+                .forwarding_fused
+                .as_ref()
+                .filter(|fused| fused.exception.is_none())
+            {
+                results.push(ForwardedResult {
+                    alu_index,
+                    register: fused.reg,
+                    value: fused.value,
+                    producer_pc: fused.pc,
+                });
+            }
+        }
+        if self.config.forwarding_bubble {
+            results = std::mem::replace(&mut self.delayed_forwards, results);
+        }
+        results
+    }
+
+    /// Forwards a result of an ALU to the integer queue, tracing every woken entry.
+    fn forward_to_integer_queue(&mut self, result: ForwardedResult) {
+        let woken_pcs = self.update_integer_queue(result.register, result.value);
+        for consumer_pc in woken_pcs {
+            self.trace.forwarded.push(ForwardEvent {
+                alu_index: result.alu_index,
+                register: result.register,
+                value: result.value,
+                producer_pc: result.producer_pc,
+                consumer_pc,
+            });
         }
     }
 
     /// The integer queue checks if any of its entries are ready to be issued,
     /// and if so, updates the entries accordingly.
-    fn update_integer_queue(&mut self, forwarding_reg: u8, forwarding_value: u64, is_exception: bool) {
+    /// Returns the PCs of the entries that received the value.
+    fn update_integer_queue(&mut self, forwarding_reg: u8, forwarding_value: u64) -> Vec<u64> {
+        let mut woken_pcs = Vec::new();
         for entry in self.integer_queue.iter_mut() {
-            if !entry.op_a_is_ready && !is_exception && (entry.op_a_reg_tag == forwarding_reg) {
+            let mut is_woken = false;
+            if !entry.op_a_is_ready && (entry.op_a_reg_tag == forwarding_reg) {
                 entry.op_a_is_ready = true;
                 entry.op_a_value = forwarding_value;
                 entry.op_a_reg_tag = 0;
+                is_woken = true;
             }
-            if !entry.op_b_is_ready && !is_exception && (entry.op_b_reg_tag == forwarding_reg) {
+            if !entry.op_b_is_ready && (entry.op_b_reg_tag == forwarding_reg) {
                 entry.op_b_is_ready = true;
                 entry.op_b_value = forwarding_value;
                 entry.op_b_reg_tag = 0;
+                is_woken = true;
+            }
+            if is_woken {
+                woken_pcs.push(entry.pc);
             }
         }
+        woken_pcs
     }
 
     /// Pushes an integer queue entry of the given decoded instruction to the integer queue.
-    fn add_integer_queue_entry(&mut self, decoded_instruction: &DecodedInstruction) {
-        let (physical_op_a_reg_tag, op_a_ready, op_a_value) =
-            self.get_operand_info(decoded_instruction.op_a_reg_tag, false, 0);
+    /// Both operands are captured the same way, through `get_operand_info`. An operand A the
+    /// instruction does not read is ready from the start, without waiting on the register it names
+    /// or using a read port.
+    /// Fails, leaving the processor untouched, if the register file read ports left this cycle
+    /// cannot capture the ready operands, or if no free register is left to rename the destination.
+    fn add_integer_queue_entry(
+        &mut self,
+        decoded_instruction: &DecodedInstruction,
+    ) -> Result<(), DispatchStall> {
+        let reads_operand_a = decoded_instruction.reads_operand_a();
+        let (physical_op_a_reg_tag, op_a_ready, op_a_value) = if reads_operand_a {
+            self.get_operand_info(decoded_instruction.op_a_reg_tag, false, 0)
+        } else {
+            (0, true, 0)
+        };
 
         let (physical_op_b_reg_tag, op_b_ready, op_b_value) = self.get_operand_info(
             decoded_instruction.op_b_reg_tag,
             decoded_instruction.immediate,
+            // Negative immediates wrap around in the 64-bit operand.
             decoded_instruction.immediate_value as u64,
         );
 
-        let physical_dest_register =
-            self.map_destination_register(decoded_instruction.logical_destination);
+        // Every ready register operand is read from the register file, immediates are not
+        let register_file_reads = (op_a_ready && reads_operand_a) as usize
+            + (op_b_ready && !decoded_instruction.immediate) as usize;
+        if self.register_file_reads + register_file_reads > self.config.read_ports {
+            return Err(DispatchStall::ReadPorts);
+        }
+
+        // Renaming the destination may remap a source, so the source registers are read first
+        let mut source_registers = Vec::new();
+        if reads_operand_a {
+            source_registers.push(self.map_register(decoded_instruction.op_a_reg_tag));
+        }
+        if !decoded_instruction.immediate {
+            source_registers.push(self.map_register(decoded_instruction.op_b_reg_tag));
+        }
+
+        let physical_dest_register = if decoded_instruction.writes_register {
+            self.map_destination_register(decoded_instruction.logical_destination)
+                .ok_or(DispatchStall::FreeList)?
+        } else {
+            0 // Stores and branches do not write a register
+        };
 
-        self.integer_queue.push(IntegerQueueEntry::new(
+        let mut entry = IntegerQueueEntry::new(
             physical_dest_register,
             op_a_ready,
             physical_op_a_reg_tag,
@@ -353,23 +1748,52 @@ impl Processor {
             op_b_value,
             decoded_instruction.op_code.clone(),
             decoded_instruction.pc,
-        ));
+            decoded_instruction.immediate,
+            decoded_instruction.immediate_value as u64,
+            decoded_instruction.writes_register,
+        );
+        entry.source_registers = source_registers;
+        entry.dispatch_cycle = self.cycle;
+        entry.slot = self.allocate_integer_queue_slot();
+
+        // Keep the queue sorted by PC so issue finds the oldest ready entry with a single scan.
+        // Inserting after the entries of equal PC keeps the order stable.
+        let index = self
+            .integer_queue
+            .partition_point(|entry| entry.pc <= decoded_instruction.pc);
+        self.integer_queue.insert(index, entry);
+        self.register_file_reads += register_file_reads;
+        Ok(())
     }
 
     /// Pushes an active list entry of the given decoded instruction to the active list.
-    fn add_active_list_entry(&mut self, decoded_instruction: &DecodedInstruction) {
-        let old_dest_register = self.map_register(decoded_instruction.logical_destination);
-        self.active_list.push(ActiveListEntry::new(
-            false,
+    /// `old_dest_register` is the physical register the destination was mapped to before renaming.
+    fn add_active_list_entry(
+        &mut self,
+        decoded_instruction: &DecodedInstruction,
+        old_dest_register: u8,
+    ) {
+        let has_destination = decoded_instruction.writes_register
+            && !self.is_discarded_write(decoded_instruction.logical_destination);
+        let mut entry = ActiveListEntry::new(
+            !decoded_instruction.needs_alu(),
             false,
             decoded_instruction.logical_destination,
             old_dest_register,
             decoded_instruction.pc,
-        ));
+            has_destination,
+        );
+        entry.is_halt = decoded_instruction.is_halt();
+        entry.op_code = decoded_instruction.op_code.clone();
+        self.active_list.push(entry);
+        // Both structures only grow on dispatch, the integer queue entry being added first
+        self.peak_occupancy.record(self.active_list.len(), self.integer_queue.len());
     }
 
     /// Helper function to determine the physical register and readiness of an operand.
     /// If the operand is ready, the physical register tag is set to 0.
+    /// Both the readiness and the value come from this cycle's busy bit table and register file,
+    /// so a register written back this cycle is captured with its new value.
     fn get_operand_info(&self, reg_tag: u8, is_immediate: bool, immediate: u64) -> (u8, bool, u64) {
         // Immediate operands are always considered "ready" and don't have a physical register tag.
         if is_immediate {
@@ -384,10 +1808,23 @@ impl Processor {
     }
 
     /// Checks if there are enough resources to process the next four instructions.
-    fn has_sufficient_resources(&self) -> bool {
-        self.free_list.len() >= DECODED_BUFFER_SIZE
-            && self.active_list.len() + DECODED_BUFFER_SIZE <= ACTIVE_LIST_SIZE
-            && self.integer_queue.len() + DECODED_BUFFER_SIZE <= INTEGER_QUEUE_SIZE
+    /// Every exhausted resource is recorded in the stall report.
+    fn has_sufficient_resources(&mut self) -> bool {
+        let width = self.config.decoded_buffer_size;
+        let free_list_ok = self.free_list.len() >= width;
+        let active_list_ok = self.active_list.len() + width <= self.config.active_list_size;
+        let integer_queue_ok = self.free_integer_queue_slots() >= width;
+
+        if !free_list_ok {
+            self.stall_report.free_list += 1;
+        }
+        if !active_list_ok {
+            self.stall_report.active_list += 1;
+        }
+        if !integer_queue_ok {
+            self.stall_report.integer_queue += 1;
+        }
+        free_list_ok && active_list_ok && integer_queue_ok
     }
 
     /// Clear the decoded instructions and their PCs after processing
@@ -404,21 +1841,32 @@ impl Processor {
     /// Gets the next free register from the free list.
     /// The free list is a FIFO queue.
     /// This also updates the map table with the new physical register and sets the busy bit.
-    fn map_destination_register(&mut self, logical_dest: u8) -> u8 {
-        let physical_dest_register = self.get_next_free_register();
+    /// Writes to a hardwired x0 keep its permanent physical register without allocating one.
+    /// Returns None, mapping nothing, if the free list is empty.
+    fn map_destination_register(&mut self, logical_dest: u8) -> Option<u8> {
+        if self.is_discarded_write(logical_dest) {
+            return Some(self.map_register(logical_dest));
+        }
+        let physical_dest_register = self.get_next_free_register()?;
+        self.debug_assert_unused(physical_dest_register);
         self.register_map_table[logical_dest as usize] = physical_dest_register;
         self.set_busy(physical_dest_register);
-        physical_dest_register
+        Some(physical_dest_register)
     }
 
-    /// Gets the next free register from the free list.
-    fn get_next_free_register(&mut self) -> u8 {
-        self.free_list.remove(0)
+    /// Checks if writes to the logical register are discarded, i.e. it is a hardwired x0.
+    fn is_discarded_write(&self, logical_register: u8) -> bool {
+        self.config.discards_writes_to(logical_register)
+    }
+
+    /// Gets the next free register from the free list, or None if it is empty.
+    fn get_next_free_register(&mut self) -> Option<u8> {
+        self.free_list.pop_front()
     }
 
     /// Checks if busy bit is set for a register.
     fn register_is_ready(&self, register: u8) -> bool {
-        self.busy_bit_table[register as usize] == false
+        !self.busy_bit_table[register as usize]
     }
 
     /// Sets the busy bit for a register.
@@ -438,8 +1886,289 @@ impl Processor {
         }
     }
 
-    /// Resets integer queue
+    /// Resets integer queue, dropping the results held back by the forwarding bubble
     fn reset_integer_queue(&mut self) {
         self.integer_queue.clear();
+        self.delayed_forwards.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_config() -> ProcessorConfig {
+        ProcessorConfig {
+            integer_queue_size: 8,
+            active_list_size: 8,
+            physical_register_file_size: 40,
+            alu_count: 2,
+            ..ProcessorConfig::default()
+        }
+    }
+
+    #[test]
+    fn with_config_sizes_the_register_structures() {
+        let processor = Processor::with_config(small_config());
+
+        assert_eq!(processor.busy_bit_table.len(), 40);
+        assert_eq!(processor.physical_register_file.len(), 40);
+        assert_eq!(processor.register_map_table.len(), REGISTER_MAP_TABLE_SIZE as usize);
+        assert_eq!(processor.free_list, (32..40).collect::<VecDeque<u8>>());
+        assert_eq!(processor.alus.len(), 2);
+    }
+
+    #[test]
+    fn resources_are_checked_against_the_configuration() {
+        let mut processor = Processor::with_config(small_config());
+        assert!(processor.has_sufficient_resources());
+
+        // Three registers left of the eight, short of a full decoded buffer of four
+        processor.free_list.drain(..5);
+        assert!(!processor.has_sufficient_resources());
+        assert_eq!(processor.stall_report.free_list, 1);
+        assert_eq!(processor.stall_report.active_list, 0);
+        assert_eq!(processor.stall_report.integer_queue, 0);
+    }
+
+    #[test]
+    fn committed_count_leaves_out_the_exception_and_the_rolled_back_instructions() {
+        let program = ["addi x1, x0, 1", "addi x2, x0, 2", "divu x3, x1, x0", "addi x4, x0, 3"];
+        let instructions = crate::parse_program(&program.map(String::from)).unwrap();
+
+        let state_log = crate::run_on(Processor::new(), &instructions, crate::MAX_CYCLES).unwrap();
+
+        let final_state = state_log.last().unwrap();
+        assert!(final_state.is_done());
+        assert_eq!(final_state.committed_instructions(), 2);
+    }
+
+    #[test]
+    fn committed_count_covers_every_instruction_of_a_run_without_exception() {
+        let program = ["addi x1, x0, 1", "mulu x2, x1, x1", "sub x3, x2, x1"];
+        let instructions = crate::parse_program(&program.map(String::from)).unwrap();
+
+        let state_log = crate::run_on(Processor::new(), &instructions, crate::MAX_CYCLES).unwrap();
+
+        assert_eq!(state_log.last().unwrap().committed_instructions(), 3);
+    }
+
+    /// Runs a `mulu` of the given latency followed by instructions all waiting on its result.
+    fn stall_report_behind_mulu(config: ProcessorConfig) -> StallReport {
+        let mut program = vec!["mulu x1, x0, x0".to_string()];
+        program.extend((2..10).map(|register| format!("add x{}, x1, x1", register)));
+        let instructions = crate::parse_program(&program).unwrap();
+        let processor = Processor::with_config(ProcessorConfig {
+            mulu_latency: 12,
+            ..config
+        });
+
+        let state_log = crate::run_on(processor, &instructions, crate::MAX_CYCLES).unwrap();
+
+        assert!(state_log.last().unwrap().has_completed(&instructions));
+        state_log.last().unwrap().stall_report()
+    }
+
+    #[test]
+    fn full_active_list_is_reported_as_its_own_stall() {
+        let stall_report = stall_report_behind_mulu(ProcessorConfig {
+            active_list_size: 4,
+            ..ProcessorConfig::default()
+        });
+
+        assert!(stall_report.active_list > 0);
+        assert_eq!(stall_report.free_list, 0);
+        assert_eq!(stall_report.integer_queue, 0);
+    }
+
+    #[test]
+    fn full_integer_queue_is_reported_as_its_own_stall() {
+        let stall_report = stall_report_behind_mulu(ProcessorConfig {
+            integer_queue_size: 4,
+            ..ProcessorConfig::default()
+        });
+
+        assert!(stall_report.integer_queue > 0);
+        assert_eq!(stall_report.free_list, 0);
+        assert_eq!(stall_report.active_list, 0);
+    }
+
+    #[test]
+    fn run_with_enough_resources_never_stalls() {
+        let stall_report = stall_report_behind_mulu(ProcessorConfig::default());
+
+        assert_eq!(stall_report.free_list, 0);
+        assert_eq!(stall_report.active_list, 0);
+        assert_eq!(stall_report.integer_queue, 0);
+    }
+
+    #[test]
+    fn free_list_allocates_from_the_front_and_recycles_to_the_back() {
+        let mut processor = Processor::new();
+
+        assert_eq!(processor.map_destination_register(1), Some(32));
+        assert_eq!(processor.map_destination_register(1), Some(33));
+        assert_eq!(processor.free_list.front(), Some(&34));
+
+        let instructions =
+            crate::parse_program(&["addi x1, x0, 1", "addi x1, x0, 2"].map(String::from)).unwrap();
+        let state_log = crate::run_on(Processor::new(), &instructions, crate::MAX_CYCLES).unwrap();
+        // The committed instructions free the registers x1 was mapped to before them, in order
+        let expected: Vec<u8> = (34..64).chain([1, 32]).collect();
+        let final_state = state_log.last().unwrap();
+        assert_eq!(final_state.free_list, expected);
+        assert_eq!(
+            serde_json::to_value(final_state).unwrap()["FreeList"],
+            serde_json::json!(expected)
+        );
+    }
+
+    #[test]
+    fn dispatch_remaps_the_logical_destination() {
+        let program = ["addi x1, x0, 3", "mulu x2, x1, x1"];
+        let instructions = crate::parse_program(&program.map(String::from)).unwrap();
+        let mut processor = Processor::new();
+
+        // Fetched in the first cycle, renamed and dispatched in the second
+        processor.step(&instructions).unwrap();
+        assert_eq!(processor.register_map_table()[1], 1);
+        processor.step(&instructions).unwrap();
+
+        assert_eq!(processor.register_map_table()[1], 32);
+        assert_eq!(processor.register_map_table()[2], 33);
+        assert!(processor.busy_bit_table()[32] && processor.busy_bit_table()[33]);
+        assert_eq!(processor.free_list().front(), Some(&34));
+        let active_list = processor.active_list();
+        assert_eq!(active_list.len(), 2);
+        assert_eq!(
+            (active_list[0].logical_destination, active_list[0].old_destination),
+            (1, 1)
+        );
+        // The mulu reads x1 from the register the addi was just renamed to
+        let mulu = &processor.integer_queue()[1];
+        assert_eq!((mulu.op_a_reg_tag, mulu.op_a_is_ready), (32, false));
+        assert_eq!(processor.physical_register_file()[32], 0);
+        assert!(!processor.is_exception_mode());
+    }
+
+    /// Runs `addi x0, x0, 5` followed by a read of x0, returning the final state.
+    fn run_write_to_x0(hardwired_zero_register: bool) -> Processor {
+        let program = ["addi x0, x0, 5", "addi x1, x0, 1"];
+        let instructions = crate::parse_program(&program.map(String::from)).unwrap();
+        let processor = Processor::with_config(ProcessorConfig {
+            hardwired_zero_register,
+            ..ProcessorConfig::default()
+        });
+        let state_log = crate::run_on(processor, &instructions, crate::MAX_CYCLES).unwrap();
+        state_log.last().unwrap().clone()
+    }
+
+    #[test]
+    fn write_to_hardwired_x0_is_discarded() {
+        let final_state = run_write_to_x0(true);
+
+        assert_eq!(final_state.architectural_registers()[..2], [0, 1]);
+        // Only x1 took a register from the free list
+        assert_eq!(final_state.register_map_table()[0], 0);
+        assert_eq!(final_state.free_list().len(), 32);
+        assert!(!final_state.free_list().contains(&0));
+    }
+
+    #[test]
+    fn write_to_x0_is_kept_when_not_hardwired() {
+        let final_state = run_write_to_x0(false);
+
+        assert_eq!(final_state.architectural_registers()[..2], [5, 6]);
+        assert_ne!(final_state.register_map_table()[0], 0);
+    }
+
+    #[test]
+    fn decode_error_names_the_instruction_and_its_index() {
+        let instructions: Vec<Instruction> = ["addi x1, x0, 1", "mulu x2, x1, x1", "foo x3, x1, x2"]
+            .iter()
+            .map(|instruction| Instruction::new(instruction.to_string()))
+            .collect();
+        let mut processor = Processor::new();
+
+        let error = processor.step(&instructions).unwrap_err();
+
+        assert_eq!(error, "Invalid op code at index 2: foo x3, x1, x2");
+    }
+
+    #[test]
+    fn decode_error_reports_the_index_in_the_program_not_the_pc() {
+        let instructions = vec![Instruction::new("add x1, x2".to_string())];
+        let mut processor = Processor::with_config(ProcessorConfig {
+            initial_pc: 100,
+            ..ProcessorConfig::default()
+        });
+
+        let error = processor.step(&instructions).unwrap_err();
+
+        assert_eq!(error, "Invalid instruction format at index 0: add x1, x2");
+    }
+
+    #[test]
+    fn exception_jumps_to_the_configured_handler() {
+        let program = ["addi x1, x0, 1", "divu x2, x1, x0"];
+        let instructions = crate::parse_program(&program.map(String::from)).unwrap();
+        let mut processor = Processor::with_config(ProcessorConfig {
+            exception_handler_pc: 0x2000,
+            initial_exception_pc: 7,
+            ..ProcessorConfig::default()
+        });
+        assert_eq!(processor.exception_pc(), 7);
+
+        while !processor.is_exception_mode() {
+            processor.step(&instructions).unwrap();
+        }
+        assert_eq!(processor.exception_pc(), 1);
+        assert_eq!(processor.exception_cause(), Some(ExceptionCause::DivideByZero));
+        processor.step(&instructions).unwrap();
+
+        assert_eq!(processor.pc(), 0x2000);
+    }
+
+    /// Runs the first three cycles of the program, in which the instructions are fetched,
+    /// dispatched, then issued, returning the PC and ALU of every instruction issued in the third.
+    fn first_issue(program: &[&str]) -> Vec<(u64, usize)> {
+        let program: Vec<String> = program.iter().map(|x| x.to_string()).collect();
+        let instructions = crate::parse_program(&program).unwrap();
+        let mut processor = Processor::new();
+        for _ in 0..3 {
+            processor.step(&instructions).unwrap();
+        }
+        processor.issued_instructions().to_vec()
+    }
+
+    #[test]
+    fn ready_instructions_fill_the_alus_in_ascending_order() {
+        let program = ["addi x1, x0, 1", "addi x2, x0, 2", "addi x3, x0, 3", "addi x4, x0, 4"];
+        assert_eq!(first_issue(&program), [(0, 0), (1, 1), (2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn waiting_instruction_leaves_no_gap_in_the_alus() {
+        let program = ["addi x1, x0, 1", "add x2, x1, x1", "addi x3, x0, 3", "addi x4, x0, 4"];
+        assert_eq!(first_issue(&program), [(0, 0), (2, 1), (3, 2)]);
+    }
+
+    #[test]
+    fn issue_picks_the_lowest_pc_ready_instruction_of_the_sorted_queue() {
+        let mut processor = Processor::new();
+        for pc in [5, 2, 7] {
+            let decoded = Instruction::new(format!("addi x{}, x0, 1", pc)).decode(pc).unwrap();
+            assert!(processor.add_integer_queue_entry(&decoded).is_ok());
+        }
+        let queued_pcs = |processor: &Processor| -> Vec<u64> {
+            processor.integer_queue.iter().map(|entry| entry.pc).collect()
+        };
+        assert_eq!(queued_pcs(&processor), [2, 5, 7]);
+
+        assert_eq!(processor.find_oldest_ready_instruction(0).unwrap().pc, 2);
+        processor.integer_queue[0].op_a_is_ready = false;
+        assert_eq!(processor.find_oldest_ready_instruction(0).unwrap().pc, 7);
+        assert_eq!(queued_pcs(&processor), [5]);
+        assert!(processor.find_oldest_ready_instruction(0).is_none());
     }
 }
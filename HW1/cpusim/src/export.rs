@@ -0,0 +1,411 @@
+use std::fmt;
+use std::io::{self, Write};
+
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::arch_modules::{ActiveListEntry, DecodedInstruction, IntegerQueueEntry, ALU};
+use crate::architecture::{Processor, ProcessorConfig};
+
+/// Version of the JSON log format, bumped whenever the logged fields change.
+pub const LOG_FORMAT_VERSION: &str = "1";
+
+/// Size of a memory word in a binary memory image.
+const MEMORY_WORD_BYTES: usize = 8;
+
+const CSV_HEADER: &str = "Cycle,PC,FreeListLength,ActiveListLength,IntegerQueueLength,Exception";
+
+/// JSON log wrapped with the version of its format, for consumers that need a stable contract.
+#[derive(Serialize)]
+pub struct VersionedLog<'a, T: Serialize + ?Sized> {
+    version: &'static str,
+    cycles: &'a T,
+}
+
+impl<'a, T: Serialize + ?Sized> VersionedLog<'a, T> {
+    pub fn new(cycles: &'a T) -> VersionedLog<'a, T> {
+        VersionedLog {
+            version: LOG_FORMAT_VERSION,
+            cycles,
+        }
+    }
+}
+
+/// Cycle log followed by an object holding the architectural register values of the final state,
+/// keyed by register name.
+///
+/// ex: [{...}, ..., {"FinalRegisters": {"x0": 0, "x1": 5, ...}}]
+pub struct LogWithFinalRegisters<'a> {
+    cycles: &'a [Processor],
+}
+
+impl<'a> LogWithFinalRegisters<'a> {
+    pub fn new(cycles: &'a [Processor]) -> LogWithFinalRegisters<'a> {
+        LogWithFinalRegisters { cycles }
+    }
+}
+
+impl Serialize for LogWithFinalRegisters<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.cycles.len() + 1))?;
+        for state in self.cycles {
+            seq.serialize_element(state)?;
+        }
+        if let Some(final_state) = self.cycles.last() {
+            let registers = final_state.architectural_registers();
+            seq.serialize_element(&FinalRegisters {
+                registers: RegisterValues(&registers),
+            })?;
+        }
+        seq.end()
+    }
+}
+
+#[derive(Serialize)]
+struct FinalRegisters<'a> {
+    #[serde(rename = "FinalRegisters")]
+    registers: RegisterValues<'a>,
+}
+
+/// Register values keyed by register name, in register order.
+struct RegisterValues<'a>(&'a [u64]);
+
+impl Serialize for RegisterValues<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.0
+                .iter()
+                .enumerate()
+                .map(|(register, value)| (format!("x{}", register), value)),
+        )
+    }
+}
+
+/// Logged state of a cycle extended with the instructions in flight: the decoded instructions
+/// waiting for dispatch and the stages of every ALU.
+#[derive(Serialize)]
+pub struct VerboseState<'a> {
+    #[serde(flatten)]
+    state: &'a Processor,
+    #[serde(rename = "DecodedInstructions")]
+    decoded_instructions: &'a [DecodedInstruction],
+    #[serde(rename = "ALUs")]
+    alus: &'a [ALU],
+}
+
+impl<'a> VerboseState<'a> {
+    pub fn new(state: &'a Processor) -> VerboseState<'a> {
+        VerboseState {
+            state,
+            decoded_instructions: state.decoded_instructions(),
+            alus: state.alus(),
+        }
+    }
+}
+
+/// Returns the verbose view of every logged cycle.
+pub fn verbose_log(log: &[Processor]) -> Vec<VerboseState<'_>> {
+    log.iter().map(VerboseState::new).collect()
+}
+
+/// Serializes the cycle log to the pretty JSON array expected by the reference logs.
+pub fn log_to_json(log: &[Processor]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(log)
+}
+
+/// Serializes memory words to a binary image, each word as 8 little-endian bytes.
+pub fn memory_to_le_bytes(words: &[u64]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+/// Parses a binary image of 8-byte little-endian words into memory words.
+/// Fails if the image does not hold a whole number of words.
+pub fn memory_from_le_bytes(bytes: &[u8]) -> Result<Vec<u64>, &'static str> {
+    let words = bytes.chunks_exact(MEMORY_WORD_BYTES);
+    if !words.remainder().is_empty() {
+        return Err("Memory image size is not a multiple of 8 bytes");
+    }
+    Ok(words
+        .map(|word| u64::from_le_bytes(word.try_into().unwrap_or_default()))
+        .collect())
+}
+
+/// Writes one logged cycle as a JSON Lines record: the compact JSON state followed by a newline.
+/// Records are written as the simulation runs, so the log never has to be held in memory.
+pub fn write_ndjson_state(state: &Processor, w: &mut impl Write) -> io::Result<()> {
+    serde_json::to_writer(&mut *w, state)?;
+    writeln!(w)
+}
+
+/// Writes the cycle log as CSV, one row per logged cycle after the header row.
+pub fn write_csv(log: &[Processor], w: &mut impl Write) -> io::Result<()> {
+    writeln!(w, "{}", CSV_HEADER)?;
+    for (cycle, state) in log.iter().enumerate() {
+        writeln!(
+            w,
+            "{},{},{},{},{},{}",
+            cycle,
+            state.pc(),
+            state.free_list().len(),
+            state.active_list().len(),
+            state.integer_queue().len(),
+            state.is_exception_mode()
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the log as MessagePack, a compact binary encoding of the same fields as the JSON log.
+/// Structures are encoded as maps keyed by field name, so the log decodes without this crate.
+pub fn write_msgpack<T: Serialize + ?Sized>(
+    cycles: &T,
+    w: &mut impl Write,
+) -> Result<(), rmp_serde::encode::Error> {
+    cycles.serialize(&mut rmp_serde::Serializer::new(w).with_struct_map())
+}
+
+/// Converts a MessagePack log back into the pretty JSON log, keeping the order of the fields.
+pub fn write_json_from_msgpack(msgpack: &[u8], w: &mut impl Write) -> serde_json::Result<()> {
+    let mut deserializer = rmp_serde::Deserializer::new(msgpack);
+    let mut serializer = serde_json::Serializer::pretty(w);
+    serde_transcode::transcode(&mut deserializer, &mut serializer)
+}
+
+/// Returns the logged fields of `current` whose value differs from `previous`.
+pub fn state_delta(previous: &Processor, current: &Processor) -> Map<String, Value> {
+    let previous = logged_fields(previous);
+    logged_fields(current)
+        .into_iter()
+        .filter(|(field, value)| previous.get(field) != Some(value))
+        .collect()
+}
+
+/// Writes the cycle log as a JSON object keyed by cycle number. The initial state is written in
+/// full, and every following cycle only holds the fields that changed since the previous cycle.
+/// Cycles changing nothing are left out.
+pub fn write_diff(log: &[Processor], w: &mut impl Write) -> io::Result<()> {
+    let mut cycles = Map::new();
+    if let Some(initial_state) = log.first() {
+        cycles.insert("0".to_string(), Value::Object(logged_fields(initial_state)));
+    }
+    for (cycle, states) in log.windows(2).enumerate() {
+        let delta = state_delta(&states[0], &states[1]);
+        if !delta.is_empty() {
+            cycles.insert((cycle + 1).to_string(), Value::Object(delta));
+        }
+    }
+    serde_json::to_writer_pretty(w, &cycles)?;
+    Ok(())
+}
+
+/// First difference between a cycle log and a reference log.
+#[derive(Debug, PartialEq)]
+pub enum LogMismatch {
+    /// A field of a cycle differs, identified by its path, e.g. "ActiveList[2].Done".
+    Field {
+        cycle: usize,
+        field: String,
+        expected: Value,
+        logged: Value,
+    },
+    /// Every common cycle matches, but the logs do not hold the same number of cycles.
+    CycleCount { expected: usize, logged: usize },
+}
+
+impl fmt::Display for LogMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LogMismatch::Field {
+                cycle,
+                field,
+                expected,
+                logged,
+            } => write!(
+                f,
+                "Cycle {}, {}: expected {}, found {}",
+                cycle, field, expected, logged
+            ),
+            LogMismatch::CycleCount { expected, logged } => write!(
+                f,
+                "Expected {} cycles, found {}",
+                expected, logged
+            ),
+        }
+    }
+}
+
+/// Compares the cycle log to a reference JSON log, cycle by cycle, returning the first difference.
+/// Like the reference logs comparison, fields missing from the reference are not compared.
+pub fn first_mismatch(log: &[Processor], reference: &[Value]) -> Option<LogMismatch> {
+    for (cycle, (state, expected)) in log.iter().zip(reference).enumerate() {
+        let logged = Value::Object(logged_fields(state));
+        if let Some((field, expected, logged)) = first_difference(&logged, expected, "") {
+            return Some(LogMismatch::Field {
+                cycle,
+                field,
+                expected: expected.clone(),
+                logged: logged.clone(),
+            });
+        }
+    }
+    (log.len() != reference.len()).then_some(LogMismatch::CycleCount {
+        expected: reference.len(),
+        logged: log.len(),
+    })
+}
+
+/// Returns the path of the first value of `expected` that `logged` does not match, with both
+/// values. Objects only compare the fields of `expected`, arrays compare every element.
+fn first_difference<'a>(
+    logged: &'a Value,
+    expected: &'a Value,
+    path: &str,
+) -> Option<(String, &'a Value, &'a Value)> {
+    match (logged, expected) {
+        (Value::Object(logged_fields), Value::Object(expected_fields)) => {
+            expected_fields.iter().find_map(|(field, expected_value)| {
+                let field_path = if path.is_empty() {
+                    field.clone()
+                } else {
+                    format!("{}.{}", path, field)
+                };
+                match logged_fields.get(field) {
+                    Some(logged_value) => first_difference(logged_value, expected_value, &field_path),
+                    None => Some((field_path, expected_value, &Value::Null)),
+                }
+            })
+        }
+        (Value::Array(logged_items), Value::Array(expected_items))
+            if logged_items.len() == expected_items.len() =>
+        {
+            logged_items
+                .iter()
+                .zip(expected_items)
+                .enumerate()
+                .find_map(|(index, (logged_item, expected_item))| {
+                    first_difference(logged_item, expected_item, &format!("{}[{}]", path, index))
+                })
+        }
+        _ if logged == expected => None,
+        _ => Some((path.to_string(), expected, logged)),
+    }
+}
+
+/// Logged fields of a cycle the invariants of a saved log are checked on. Other fields are ignored.
+#[derive(Deserialize)]
+struct LoggedState {
+    #[serde(rename = "ActiveList")]
+    active_list: Vec<ActiveListEntry>,
+    #[serde(rename = "BusyBitTable")]
+    busy_bit_table: Vec<bool>,
+    #[serde(rename = "FreeList")]
+    free_list: Vec<u8>,
+    #[serde(rename = "IntegerQueue")]
+    integer_queue: Vec<IntegerQueueEntry>,
+    #[serde(rename = "RegisterMapTable")]
+    register_map_table: Vec<u8>,
+}
+
+/// Checks the invariants of every cycle of a saved JSON log, without simulating the program
+/// again. Fails with the first cycle breaking one of them:
+/// - the active list and the integer queue hold at most `active_list_size` and
+///   `integer_queue_size` entries,
+/// - the busy bit table holds a bit per physical register,
+/// - no physical register is both free and mapped, nor free or mapped several times,
+/// - every physical register is free, mapped, or the old destination of an in-flight instruction,
+///   which frees it when it retires. The log does not tell which instructions have a destination,
+///   except with a hardwired x0, whose writes are discarded: instructions writing x0, as well as
+///   nops, stores and branches, then free nothing when they retire,
+/// - a busy register is not free, but mapped or the old destination of an in-flight instruction.
+pub fn validate_log(json: &str, config: &ProcessorConfig) -> Result<(), String> {
+    let log: Vec<LoggedState> =
+        serde_json::from_str(json).map_err(|error| format!("Invalid log file: {}", error))?;
+    for (cycle, state) in log.iter().enumerate() {
+        validate_state(state, config).map_err(|error| format!("Cycle {}: {}", cycle, error))?;
+    }
+    Ok(())
+}
+
+/// Checks the invariants of a single logged cycle, see `validate_log`.
+fn validate_state(state: &LoggedState, config: &ProcessorConfig) -> Result<(), String> {
+    if state.active_list.len() > config.active_list_size {
+        return Err(format!(
+            "Active list holds {} entries, above its size of {}",
+            state.active_list.len(),
+            config.active_list_size
+        ));
+    }
+    if state.integer_queue.len() > config.integer_queue_size {
+        return Err(format!(
+            "Integer queue holds {} entries, above its size of {}",
+            state.integer_queue.len(),
+            config.integer_queue_size
+        ));
+    }
+    let register_count = config.physical_register_file_size;
+    if state.busy_bit_table.len() != register_count {
+        return Err(format!(
+            "Busy bit table holds {} registers instead of {}",
+            state.busy_bit_table.len(),
+            register_count
+        ));
+    }
+
+    let mut is_free = vec![false; register_count];
+    let mut is_mapped = vec![false; register_count];
+    for (registers, is_owned, owner) in [
+        (&state.free_list, &mut is_free, "free"),
+        (&state.register_map_table, &mut is_mapped, "mapped"),
+    ] {
+        for &register in registers {
+            match is_owned.get_mut(register as usize) {
+                None => return Err(format!("Physical register {} is out of range", register)),
+                Some(true) => {
+                    return Err(format!("Physical register {} is {} several times", register, owner))
+                }
+                Some(is_owned) => *is_owned = true,
+            }
+        }
+    }
+    let mut is_old_destination = vec![false; register_count];
+    let has_destination =
+        |entry: &&ActiveListEntry| !config.discards_writes_to(entry.logical_destination);
+    for entry in state.active_list.iter().filter(has_destination) {
+        if let Some(is_old) = is_old_destination.get_mut(entry.old_destination as usize) {
+            *is_old = true;
+        }
+    }
+
+    let both_free_and_mapped =
+        (0..register_count).find(|&register| is_free[register] && is_mapped[register]);
+    if let Some(register) = both_free_and_mapped {
+        return Err(format!("Physical register {} is both free and mapped", register));
+    }
+    for register in 0..register_count {
+        let is_held = is_mapped[register] || is_old_destination[register];
+        if !is_free[register] && !is_held {
+            return Err(format!(
+                "Physical register {} is neither free, mapped, nor the old destination of an \
+                 in-flight instruction",
+                register
+            ));
+        }
+        if state.busy_bit_table[register] && (is_free[register] || !is_held) {
+            return Err(format!(
+                "Physical register {} is busy without being the destination of an in-flight \
+                 instruction",
+                register
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Serializes the processor state to its logged fields, as they appear in the JSON log.
+fn logged_fields(state: &Processor) -> Map<String, Value> {
+    match serde_json::to_value(state) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    }
+}
@@ -0,0 +1,174 @@
+//! Seeded generator of random but valid programs, to stress the pipeline.
+//!
+//! ex: generate_program(20, 7) -> the same 20 instructions on every call
+
+/// Register-register operations, divisions excluded as their divisor is checked first.
+const REGISTER_OP_CODES: [&str; 9] = ["add", "sub", "mulu", "and", "or", "xor", "sll", "srl", "sra"];
+const DIVISION_OP_CODES: [&str; 4] = ["divu", "remu", "div", "rem"];
+const IMMEDIATE_OP_CODES: [&str; 8] = [
+    "addi", "subi", "andi", "ori", "xori", "slli", "srli", "srai",
+];
+/// Number of 64-bit words loads and stores access, from address 0, within the default memory.
+const MEMORY_WORDS: u64 = 16;
+const MEMORY_WORD_SIZE: u64 = 8;
+const REGISTER_COUNT: usize = 32;
+/// Number of recently written registers sources are preferably picked from, to build chains of
+/// dependent instructions.
+const RECENT_DESTINATIONS: usize = 4;
+
+/// Generates a program of `instruction_count` instructions from `seed`. The same seed always
+/// produces the same program.
+///
+/// The program has no branches, so it runs to its end, and raises no exception under the default
+/// configuration: registers and memory are modeled while generating so that no division by zero
+/// or overflowing signed division is emitted, and every memory access is aligned and in bounds.
+/// x0 is never written, so it can serve as the base of the memory accesses.
+pub fn generate_program(instruction_count: usize, seed: u64) -> Vec<String> {
+    let mut generator = Generator::new(seed);
+    (0..instruction_count)
+        .map(|_| generator.next_instruction())
+        .collect()
+}
+
+struct Generator {
+    rng: SplitMix64,
+    registers: [u64; REGISTER_COUNT],
+    memory: [u64; MEMORY_WORDS as usize],
+    recent_destinations: Vec<usize>,
+}
+
+impl Generator {
+    fn new(seed: u64) -> Generator {
+        Generator {
+            rng: SplitMix64(seed),
+            registers: [0; REGISTER_COUNT],
+            memory: [0; MEMORY_WORDS as usize],
+            recent_destinations: Vec::new(),
+        }
+    }
+
+    fn next_instruction(&mut self) -> String {
+        match self.rng.below(10) {
+            0..=4 => self.register_instruction(),
+            5..=7 => self.immediate_instruction(),
+            8 => self.load(),
+            _ => self.store(),
+        }
+    }
+
+    fn register_instruction(&mut self) -> String {
+        let (rd, rs1, rs2) = (self.destination(), self.source(), self.source());
+        let (a, b) = (self.registers[rs1], self.registers[rs2]);
+        let division = DIVISION_OP_CODES[self.rng.below(DIVISION_OP_CODES.len() as u64) as usize];
+        let op_code = if self.rng.below(4) == 0 && division_result(division, a, b).is_some() {
+            division
+        } else {
+            REGISTER_OP_CODES[self.rng.below(REGISTER_OP_CODES.len() as u64) as usize]
+        };
+        let value = match op_code {
+            "add" => a.wrapping_add(b),
+            "sub" => a.wrapping_sub(b),
+            "mulu" => a.wrapping_mul(b),
+            "and" => a & b,
+            "or" => a | b,
+            "xor" => a ^ b,
+            "sll" => a << (b & 63),
+            "srl" => a >> (b & 63),
+            "sra" => ((a as i64) >> (b & 63)) as u64,
+            _ => division_result(op_code, a, b).unwrap_or_default(),
+        };
+        self.write(rd, value);
+        format!("{} x{}, x{}, x{}", op_code, rd, rs1, rs2)
+    }
+
+    fn immediate_instruction(&mut self) -> String {
+        let (rd, rs1) = (self.destination(), self.source());
+        let op_code = IMMEDIATE_OP_CODES[self.rng.below(IMMEDIATE_OP_CODES.len() as u64) as usize];
+        let a = self.registers[rs1];
+        let (immediate, value) = if op_code.starts_with('s') && op_code != "subi" {
+            let shift = self.rng.below(64);
+            let value = match op_code {
+                "slli" => a << shift,
+                "srli" => a >> shift,
+                _ => ((a as i64) >> shift) as u64,
+            };
+            (shift as i64, value)
+        } else {
+            let immediate = self.rng.below(201) as i64 - 100;
+            let b = immediate as u64;
+            let value = match op_code {
+                "addi" => a.wrapping_add(b),
+                "subi" => a.wrapping_sub(b),
+                "andi" => a & b,
+                "ori" => a | b,
+                _ => a ^ b,
+            };
+            (immediate, value)
+        };
+        self.write(rd, value);
+        format!("{} x{}, x{}, {}", op_code, rd, rs1, immediate)
+    }
+
+    fn load(&mut self) -> String {
+        let (rd, word) = (self.destination(), self.rng.below(MEMORY_WORDS));
+        self.write(rd, self.memory[word as usize]);
+        format!("ld x{}, {}(x0)", rd, word * MEMORY_WORD_SIZE)
+    }
+
+    fn store(&mut self) -> String {
+        let (rs, word) = (self.source(), self.rng.below(MEMORY_WORDS));
+        self.memory[word as usize] = self.registers[rs];
+        format!("st x{}, {}(x0)", rs, word * MEMORY_WORD_SIZE)
+    }
+
+    /// Picks a destination register, never x0.
+    fn destination(&mut self) -> usize {
+        1 + self.rng.below(REGISTER_COUNT as u64 - 1) as usize
+    }
+
+    /// Picks a source register, half of the time among the recently written ones.
+    fn source(&mut self) -> usize {
+        if !self.recent_destinations.is_empty() && self.rng.below(2) == 0 {
+            let index = self.rng.below(self.recent_destinations.len() as u64) as usize;
+            self.recent_destinations[index]
+        } else {
+            self.rng.below(REGISTER_COUNT as u64) as usize
+        }
+    }
+
+    fn write(&mut self, register: usize, value: u64) {
+        self.registers[register] = value;
+        self.recent_destinations.push(register);
+        if self.recent_destinations.len() > RECENT_DESTINATIONS {
+            self.recent_destinations.remove(0);
+        }
+    }
+}
+
+/// Returns the result of a division, or None if it would raise an exception.
+fn division_result(op_code: &str, a: u64, b: u64) -> Option<u64> {
+    match op_code {
+        "divu" => a.checked_div(b),
+        "remu" => a.checked_rem(b),
+        "div" => (a as i64).checked_div(b as i64).map(|x| x as u64),
+        _ => (a as i64).checked_rem(b as i64).map(|x| x as u64),
+    }
+}
+
+/// Small deterministic pseudo-random number generator, so programs only depend on the seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
@@ -0,0 +1,295 @@
+//! Cycle-accurate simulator of an out-of-order processor inspired by the MIPS R10000.
+//!
+//! ex: run_program(vec!["addi x1, x0, 1".to_string()], ProcessorConfig::default())
+
+use std::collections::HashMap;
+
+use crate::arch_modules::Instruction;
+use crate::architecture::{Processor, ProcessorConfig};
+
+pub mod arch_modules;
+pub mod architecture;
+pub mod export;
+pub mod generator;
+pub mod statistics;
+pub mod trace;
+
+/// Program with the name it is logged under, e.g. the path of its input file.
+pub type NamedProgram = (String, Vec<Instruction>);
+
+/// Default cap on the number of logged cycles, including the initial state.
+pub const MAX_CYCLES: usize = 50;
+
+/// Default cap on the number of instructions of an input program.
+pub const MAX_PROGRAM_SIZE: usize = 1 << 16;
+
+/// Parses the entries of an instruction file into the program, one instruction per PC, for a
+/// program loaded at the default initial PC.
+pub fn parse_program(instruction_strings: &[String]) -> Result<Vec<Instruction>, String> {
+    parse_program_at(instruction_strings, ProcessorConfig::default().initial_pc)
+}
+
+/// Parses the entries of an instruction file into the program loaded at `initial_pc`, one
+/// instruction per PC. Comments, blank entries and label definitions like "loop:" are not
+/// instructions and do not take a PC. Branch and jump targets naming a label are resolved to the
+/// PC of the instruction following its definition.
+/// Fails on an undefined or duplicate label.
+pub fn parse_program_at(
+    instruction_strings: &[String],
+    initial_pc: u64,
+) -> Result<Vec<Instruction>, String> {
+    let mut labels: HashMap<&str, u64> = HashMap::new();
+    let mut instructions: Vec<Instruction> = Vec::new();
+    for entry in instruction_strings.iter().filter(|x| !is_comment_or_blank(x)) {
+        match Instruction::label_definition(entry) {
+            Some(label) => {
+                let pc = initial_pc + instructions.len() as u64;
+                if labels.insert(label, pc).is_some() {
+                    return Err(format!("Duplicate label: {}", label));
+                }
+            }
+            None => instructions.push(Instruction::new(entry.to_string())),
+        }
+    }
+
+    instructions
+        .into_iter()
+        .map(|instruction| match instruction.target_label() {
+            Some(label) => match labels.get(label) {
+                Some(&target) => Ok(instruction.with_target(target)),
+                None => Err(format!("Undefined label: {}", label)),
+            },
+            None => Ok(instruction),
+        })
+        .collect()
+}
+
+/// Checks that the program holds at most `max_program_size` instructions.
+pub fn check_program_size(
+    instructions: &[Instruction],
+    max_program_size: usize,
+) -> Result<(), String> {
+    if instructions.len() > max_program_size {
+        return Err(format!(
+            "Program has {} instructions, more than the maximum of {}",
+            instructions.len(),
+            max_program_size
+        ));
+    }
+    Ok(())
+}
+
+/// Parses the contents of a JSON instruction file, an array of instruction strings, into the
+/// program, like `parse_program`.
+///
+/// ex: parse_instructions_from_json(r#"["addi x1, x0, 1", "nop"]"#) -> 2 instructions
+pub fn parse_instructions_from_json(json: &str) -> Result<Vec<Instruction>, String> {
+    let instruction_strings: Vec<String> = serde_json::from_str(json)
+        .map_err(|error| format!("Invalid instruction file: {}", error))?;
+    parse_program(&instruction_strings)
+}
+
+/// Parses a register seed given as "<register>=<value>", the register by its name and the value
+/// as an immediate.
+///
+/// ex: parse_register_seed("x10=5") -> (10, 5)
+/// ex: parse_register_seed("a1=-0x10") -> (11, -16 as u64)
+pub fn parse_register_seed(seed: &str) -> Result<(u8, u64), String> {
+    let (register_str, value_str) = seed
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid register seed, expected <register>=<value>: {}", seed))?;
+    let register = Instruction::parse_register(register_str.trim())
+        .map_err(|error| format!("{}: {}", error, seed))?;
+    let value = Instruction::parse_immediate(value_str.trim())
+        .ok_or_else(|| format!("Invalid register value: {}", seed))?;
+    Ok((register, value as u64))
+}
+
+/// Parses an assembly source into the program, one instruction per line.
+/// Like in the JSON format, comment, blank and label lines do not take a PC.
+pub fn parse_assembly(source: &str) -> Result<Vec<Instruction>, String> {
+    let lines: Vec<String> = source.lines().map(|line| line.to_string()).collect();
+    parse_program(&lines)
+}
+
+/// Simulates the program on a processor built from `config` until it completes or `max_cycles`
+/// states are logged. Returns the state log, starting with the initial state.
+/// Fails if a fetched instruction cannot be decoded, or if the processor stops making progress.
+pub fn run(
+    instructions: &[Instruction],
+    config: ProcessorConfig,
+    max_cycles: usize,
+) -> Result<Vec<Processor>, String> {
+    run_on(Processor::with_config(config), instructions, max_cycles)
+}
+
+/// Simulates the program like `run`, starting from a prepared processor instead of a fresh one,
+/// e.g. with its memory initialized.
+pub fn run_on(
+    processor: Processor,
+    instructions: &[Instruction],
+    max_cycles: usize,
+) -> Result<Vec<Processor>, String> {
+    simulate(processor, instructions, max_cycles, false, false)
+}
+
+/// Simulates the program like `run_on`, also stopping at the first simulated cycle in which the
+/// processor is fully idle, even if instructions are left, e.g. to isolate the prologue run before
+/// an exception handler.
+pub fn run_on_until_idle(
+    processor: Processor,
+    instructions: &[Instruction],
+    max_cycles: usize,
+) -> Result<Vec<Processor>, String> {
+    simulate(processor, instructions, max_cycles, true, false)
+}
+
+/// Simulates the program like `run_on`, for as many cycles, but only keeps the final state. The
+/// returned log holds that single state, so long runs do not accumulate every cycle in memory.
+pub fn run_on_final_only(
+    processor: Processor,
+    instructions: &[Instruction],
+    max_cycles: usize,
+) -> Result<Vec<Processor>, String> {
+    simulate(processor, instructions, max_cycles, false, true)
+}
+
+/// Simulates the program like `run_on`, handing every state to `on_state` as soon as it is
+/// simulated instead of logging it, e.g. to write it out, so no state log builds up in memory.
+/// Returns the final state. Fails with the first error of `on_state` as well.
+pub fn stream_on(
+    processor: Processor,
+    instructions: &[Instruction],
+    max_cycles: usize,
+    on_state: impl FnMut(&Processor) -> Result<(), String>,
+) -> Result<Processor, String> {
+    simulate_streaming(processor, instructions, max_cycles, false, on_state)
+}
+
+/// Streams the states of the program like `stream_on`, also stopping at the first fully idle
+/// cycle like `run_on_until_idle`.
+pub fn stream_on_until_idle(
+    processor: Processor,
+    instructions: &[Instruction],
+    max_cycles: usize,
+    on_state: impl FnMut(&Processor) -> Result<(), String>,
+) -> Result<Processor, String> {
+    simulate_streaming(processor, instructions, max_cycles, true, on_state)
+}
+
+/// Simulates the program, logging every state, or only the last one if `final_only` is set.
+/// The cycle cap counts every simulated state either way.
+fn simulate(
+    processor: Processor,
+    instructions: &[Instruction],
+    max_cycles: usize,
+    until_idle: bool,
+    final_only: bool,
+) -> Result<Vec<Processor>, String> {
+    let mut state_log: Vec<Processor> = Vec::new();
+    simulate_streaming(processor, instructions, max_cycles, until_idle, |state| {
+        if final_only {
+            state_log.clear();
+        }
+        state.log_state(&mut state_log);
+        Ok(())
+    })?;
+    Ok(state_log)
+}
+
+/// Simulates the program, handing the initial state and every simulated one to `on_state`.
+/// Returns the final state.
+fn simulate_streaming(
+    mut processor: Processor,
+    instructions: &[Instruction],
+    max_cycles: usize,
+    until_idle: bool,
+    mut on_state: impl FnMut(&Processor) -> Result<(), String>,
+) -> Result<Processor, String> {
+    // Log the initial state
+    on_state(&processor)?;
+    let mut simulated_states = 1;
+
+    while !processor.has_completed(instructions) && (simulated_states < max_cycles) {
+        processor.step(instructions)?;
+        simulated_states += 1;
+        on_state(&processor)?;
+        if until_idle && processor.is_fully_idle() {
+            break;
+        }
+    }
+    Ok(processor)
+}
+
+/// Simulates several named programs back to back, each on a fresh processor built from
+/// `config`, returning the state log of every program under its name.
+pub fn run_programs(
+    programs: &[NamedProgram],
+    config: ProcessorConfig,
+    max_cycles: usize,
+) -> Result<Vec<(String, Vec<Processor>)>, String> {
+    run_programs_on(programs, &Processor::with_config(config), max_cycles)
+}
+
+/// Simulates several named programs back to back like `run_programs`, each on a copy of the
+/// prepared `initial_state`.
+pub fn run_programs_on(
+    programs: &[NamedProgram],
+    initial_state: &Processor,
+    max_cycles: usize,
+) -> Result<Vec<(String, Vec<Processor>)>, String> {
+    run_each(programs, |instructions| run_on(initial_state.clone(), instructions, max_cycles))
+}
+
+/// Simulates several named programs back to back like `run_programs_on`, each stopping at its
+/// first fully idle cycle like `run_on_until_idle`.
+pub fn run_programs_until_idle_on(
+    programs: &[NamedProgram],
+    initial_state: &Processor,
+    max_cycles: usize,
+) -> Result<Vec<(String, Vec<Processor>)>, String> {
+    run_each(programs, |instructions| {
+        run_on_until_idle(initial_state.clone(), instructions, max_cycles)
+    })
+}
+
+/// Simulates several named programs back to back like `run_programs_on`, only keeping the final
+/// state of each like `run_on_final_only`.
+pub fn run_programs_final_only_on(
+    programs: &[NamedProgram],
+    initial_state: &Processor,
+    max_cycles: usize,
+) -> Result<Vec<(String, Vec<Processor>)>, String> {
+    run_each(programs, |instructions| {
+        run_on_final_only(initial_state.clone(), instructions, max_cycles)
+    })
+}
+
+/// Runs every program, naming each state log and each error after its program.
+fn run_each(
+    programs: &[NamedProgram],
+    run: impl Fn(&[Instruction]) -> Result<Vec<Processor>, String>,
+) -> Result<Vec<(String, Vec<Processor>)>, String> {
+    programs
+        .iter()
+        .map(|(name, instructions)| {
+            let state_log = run(instructions).map_err(|e| format!("{}: {}", name, e))?;
+            Ok((name.clone(), state_log))
+        })
+        .collect()
+}
+
+/// Parses and simulates the program for at most `MAX_CYCLES` logged states, returning the full
+/// state log. Fails if the program has an invalid label or an instruction cannot be decoded.
+pub fn run_program(
+    instructions: Vec<String>,
+    config: ProcessorConfig,
+) -> Result<Vec<Processor>, String> {
+    run(&parse_program_at(&instructions, config.initial_pc)?, config, MAX_CYCLES)
+}
+
+/// Checks if an instruction file entry is empty, whitespace-only, or a `#` or `//` comment.
+fn is_comment_or_blank(entry: &str) -> bool {
+    let entry = entry.trim();
+    entry.is_empty() || entry.starts_with('#') || entry.starts_with("//")
+}
@@ -1,76 +1,669 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::error::Error;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use crate::arch_modules::Instruction;
+use cpusim::arch_modules::Instruction;
+use cpusim::architecture::{Processor, ProcessorConfig};
+use cpusim::statistics::Statistics;
+use cpusim::{export, generator, NamedProgram, MAX_CYCLES, MAX_PROGRAM_SIZE};
+use serde::Serialize;
+use serde_json::Value;
 
-mod arch_modules;
-pub mod architecture;
+/// Path argument standing for the standard input or output stream.
+const STDIO_PATH: &str = "-";
+/// Separator of the input files of several programs simulated back to back.
+const INPUT_SEPARATOR: char = ',';
+/// Flag printing a human-readable pipeline trace of every cycle to stderr.
+const TRACE_FLAG: &str = "--trace";
+/// Flag printing the values forwarded from the ALUs to the integer queue every cycle to stderr.
+const TRACE_FORWARDING_FLAG: &str = "--trace-forwarding";
+/// Flag only decoding the program, reporting every invalid instruction, without simulating it.
+const CHECK_FLAG: &str = "--check";
+/// Flag reading the input as assembly text, one instruction per line, instead of a JSON array.
+/// Implied by a `.s` input file.
+const ASM_FLAG: &str = "--asm";
+/// Extension of assembly input files.
+const ASM_EXTENSION: &str = "s";
+/// Flag selecting the format of the output log, followed by "json" (default), "csv", "diff",
+/// "msgpack" or "ndjson".
+const FORMAT_FLAG: &str = "--format";
+/// Flag adding the decoded instructions and ALU stages of every cycle to the JSON log.
+const VERBOSE_FLAG: &str = "--verbose";
+/// Flag wrapping the JSON log in an object holding the log format version and the cycles.
+/// The bare array stays the default, as expected by the reference logs comparison.
+const VERSIONED_FLAG: &str = "--versioned";
+/// Flag followed by a binary file of little-endian 64-bit words the data memory is initialized
+/// from.
+const MEM_INIT_FLAG: &str = "--mem-init";
+/// Flag followed by the binary file the final data memory is dumped to, as little-endian 64-bit
+/// words.
+const MEM_DUMP_FLAG: &str = "--mem-dump";
+/// Flag followed by "<register>=<value>", e.g. "x10=5", writing an input value to a logical
+/// register before the program runs. Repeated to seed several registers.
+const REG_FLAG: &str = "--reg";
+/// Flag appending to the JSON log an object holding the value of every logical register in the
+/// final state.
+const FINAL_REGISTERS_FLAG: &str = "--final-registers";
+/// Flags simulating every cycle but only logging the final state of each program, for long runs
+/// whose intermediate states are not needed.
+const QUIET_FLAGS: [&str; 2] = ["--quiet", "--final-only"];
+/// Flags needing the state of every cycle, which the quiet flags do not keep.
+const EVERY_CYCLE_FLAGS: [&str; 3] = [TRACE_FLAG, TRACE_FORWARDING_FLAG, LOG_UNTIL_IDLE_FLAG];
+/// Flag stopping the simulation of a program at its first fully idle cycle, with no instruction in
+/// flight, even if instructions are left to run.
+const LOG_UNTIL_IDLE_FLAG: &str = "--log-until-idle";
+/// Flag converting the MessagePack log given as input back into the JSON log, without simulating.
+const DECODE_LOG_FLAG: &str = "--decode-log";
+/// Flag followed by a saved JSON log whose invariants are checked cycle by cycle, without
+/// simulating.
+const VALIDATE_LOG_FLAG: &str = "--validate-log";
+/// Flag followed by a reference JSON log the produced log is compared to. The first difference
+/// is reported and the run fails.
+const EXPECT_FLAG: &str = "--expect";
+/// Flag followed by the maximum number of instructions of an input program, `MAX_PROGRAM_SIZE`
+/// by default.
+const MAX_PROGRAM_SIZE_FLAG: &str = "--max-program-size";
+/// Command writing a random program instead of simulating one:
+/// `cpusim generate <output> <instruction count> [--seed <seed>]`.
+const GENERATE_COMMAND: &str = "generate";
+/// Flag followed by the seed of the generated program, 0 by default.
+const SEED_FLAG: &str = "--seed";
+/// Exit code of a run stopped by the cycle cap before every program completed, distinct from the
+/// exit code of a failed run.
+const TRUNCATED_EXIT_CODE: u8 = 2;
+/// Flags followed by a value, which is not a positional argument.
+const VALUE_FLAGS: [&str; 8] = [
+    FORMAT_FLAG,
+    MEM_INIT_FLAG,
+    MEM_DUMP_FLAG,
+    SEED_FLAG,
+    EXPECT_FLAG,
+    REG_FLAG,
+    VALIDATE_LOG_FLAG,
+    MAX_PROGRAM_SIZE_FLAG,
+];
 
-const MAX_CYCLES: usize = 50;
+/// Format the cycle log is written in.
+enum LogFormat {
+    /// Full processor state of every cycle, as expected by the reference logs.
+    Json,
+    /// Full processor state of every cycle, including the instructions in flight.
+    VerboseJson,
+    /// One row per cycle of the main structure occupancies.
+    Csv,
+    /// Only the fields changed since the previous cycle, keyed by cycle number.
+    Diff,
+    /// Full processor state of every cycle in the compact binary MessagePack encoding.
+    MessagePack,
+    /// Full processor state of every cycle as JSON Lines, one compact object per line, written
+    /// as the simulation runs.
+    Ndjson,
+}
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let mut instructions = parse_input()?;
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    if optional_argument(1).as_deref() == Some(GENERATE_COMMAND) {
+        generate_program()?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    if has_flag(DECODE_LOG_FLAG) {
+        decode_log()?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    if let Some(log_path) = flag_value(VALIDATE_LOG_FLAG) {
+        validate_log(&log_path)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    let programs = parse_inputs()?;
+    if has_flag(CHECK_FLAG) {
+        for (name, instructions) in &programs {
+            if programs.len() > 1 {
+                eprintln!("Program {}:", name);
+            }
+            check_program(instructions)?;
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+    let max_cycles = parse_max_cycles()?;
+    let format = parse_format()?;
+    let versioned = has_flag(VERSIONED_FLAG);
 
-    // Initialize the processor state
-    let mut state_log: Vec<architecture::Processor> = Vec::new();
-    let mut processor = architecture::Processor::new();
+    let initial_state = initial_state()?;
+    let quiet = QUIET_FLAGS.iter().any(|flag| has_flag(flag));
+    if matches!(format, LogFormat::Ndjson) && !quiet {
+        return stream_ndjson_log(&programs, &initial_state, max_cycles);
+    }
+    let logs = if quiet {
+        if let Some(flag) = EVERY_CYCLE_FLAGS.iter().find(|flag| has_flag(flag)) {
+            return Err(format!(
+                "{} needs every cycle, it cannot be combined with {}",
+                flag, QUIET_FLAGS[0]
+            )
+            .into());
+        }
+        cpusim::run_programs_final_only_on(&programs, &initial_state, max_cycles)?
+    } else if has_flag(LOG_UNTIL_IDLE_FLAG) {
+        cpusim::run_programs_until_idle_on(&programs, &initial_state, max_cycles)?
+    } else {
+        cpusim::run_programs_on(&programs, &initial_state, max_cycles)?
+    };
+    for ((name, instructions), (_, state_log)) in programs.iter().zip(&logs) {
+        if programs.len() > 1 {
+            eprintln!("Program {}:", name);
+        }
+        report_run(instructions, state_log, max_cycles);
+    }
 
-    // Log the initial state
-    processor.log_state(&mut state_log);
+    if let Some(dump_path) = flag_value(MEM_DUMP_FLAG) {
+        dump_memory(&logs, &dump_path)?;
+    }
+    match logs.as_slice() {
+        [(_, state_log)] if has_flag(FINAL_REGISTERS_FLAG) => {
+            save_log_with_final_registers(state_log, &format, versioned)?
+        }
+        [(_, state_log)] => save_log(state_log, &format, versioned)?,
+        _ if has_flag(FINAL_REGISTERS_FLAG) => {
+            return Err("Only the log of a single input program can hold the final registers".into())
+        }
+        _ => save_combined_log(&logs, &format, versioned)?,
+    }
+    if let Some(reference_path) = flag_value(EXPECT_FLAG) {
+        check_expected_log(&logs, &reference_path)?;
+    }
 
-    while !(instructions.is_empty() && processor.is_done()) && (state_log.len() < MAX_CYCLES)
-    {
-        let new_processor_state = processor.propagate(&mut instructions);
-        processor.latch(&new_processor_state);
-        processor.log_state(&mut state_log);
+    let is_truncated = programs
+        .iter()
+        .zip(&logs)
+        .any(|((_, instructions), (_, state_log))| is_truncated(instructions, state_log));
+    if is_truncated {
+        return Ok(ExitCode::from(TRUNCATED_EXIT_CODE));
     }
+    Ok(ExitCode::SUCCESS)
+}
 
-    save_log(&state_log)?;
+/// Simulates the single input program, writing every state to the output as a JSON Lines record
+/// as soon as it is simulated, so the state log is never held in memory. Reports the run from its
+/// final state.
+fn stream_ndjson_log(
+    programs: &[NamedProgram],
+    initial_state: &Processor,
+    max_cycles: usize,
+) -> Result<ExitCode, Box<dyn Error>> {
+    let [(name, instructions)] = programs else {
+        return Err("Only the log of a single input program can be streamed".into());
+    };
+    // These flags need every state once the simulation is done
+    let full_log_flags = [
+        TRACE_FLAG,
+        TRACE_FORWARDING_FLAG,
+        FINAL_REGISTERS_FLAG,
+        EXPECT_FLAG,
+        VERSIONED_FLAG,
+    ];
+    if let Some(flag) = full_log_flags.iter().find(|flag| has_flag(flag)) {
+        return Err(format!("{} cannot be combined with the ndjson log format", flag).into());
+    }
 
+    let mut output: Box<dyn Write> = if argument(2)? == STDIO_PATH {
+        Box::new(io::stdout().lock())
+    } else {
+        Box::new(BufWriter::new(fs::File::create(resolve_output_path()?)?))
+    };
+    let write_state = |state: &Processor| {
+        export::write_ndjson_state(state, &mut output).map_err(|e| e.to_string())
+    };
+    let processor = initial_state.clone();
+    let final_state = if has_flag(LOG_UNTIL_IDLE_FLAG) {
+        cpusim::stream_on_until_idle(processor, instructions, max_cycles, write_state)
+    } else {
+        cpusim::stream_on(processor, instructions, max_cycles, write_state)
+    }
+    .map_err(|e| format!("{}: {}", name, e))?;
+    output.flush()?;
+
+    let logs = [(name.clone(), vec![final_state])];
+    let final_log = &logs[0].1;
+    report_run(instructions, final_log, max_cycles);
+    if let Some(dump_path) = flag_value(MEM_DUMP_FLAG) {
+        dump_memory(&logs, &dump_path)?;
+    }
+    if is_truncated(instructions, final_log) {
+        return Ok(ExitCode::from(TRUNCATED_EXIT_CODE));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Compares the log of the program to the reference log, reporting the first difference.
+/// Fails if the logs differ.
+fn check_expected_log(
+    logs: &[(String, Vec<Processor>)],
+    reference_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let [(_, state_log)] = logs else {
+        return Err("Only the log of a single input program can be compared".into());
+    };
+    let reference = fs::read_to_string(resolve_path(reference_path)?)?;
+    let reference: Vec<Value> = serde_json::from_str(&reference)?;
+    match export::first_mismatch(state_log, &reference) {
+        Some(mismatch) => {
+            eprintln!("{}", mismatch);
+            Err("Log does not match the reference".into())
+        }
+        None => {
+            eprintln!("Log matches the reference");
+            Ok(())
+        }
+    }
+}
+
+/// Checks the invariants of every cycle of a saved JSON log, reporting the first broken one.
+fn validate_log(log_path: &str) -> Result<(), Box<dyn Error>> {
+    let log = fs::read_to_string(resolve_path(log_path)?)?;
+    export::validate_log(&log, &ProcessorConfig::default())?;
+    eprintln!("Log is consistent");
     Ok(())
 }
 
-fn parse_input() -> Result<Vec<Instruction>, Box<dyn Error>> {
-    let input_file = resolve_input_path()?;
-    let json_data = fs::read_to_string(input_file.as_path())?;
-    let instruction_strings: Vec<String> = serde_json::from_str(&json_data)?;
-    let mut instructions: Vec<Instruction> = instruction_strings
-        .iter()
-        .map(|x| Instruction::new(x.to_string()))
-        .collect();
-    instructions.reverse();
-    Ok(instructions)
+/// Converts the MessagePack log of the input argument into the JSON log.
+fn decode_log() -> Result<(), Box<dyn Error>> {
+    let input_path = argument(1)?;
+    let msgpack = if input_path == STDIO_PATH {
+        let mut msgpack = Vec::new();
+        io::stdin().read_to_end(&mut msgpack)?;
+        msgpack
+    } else {
+        fs::read(resolve_path(&input_path)?)?
+    };
+    let mut json = Vec::new();
+    export::write_json_from_msgpack(&msgpack, &mut json)?;
+    write_output(&String::from_utf8(json)?)
+}
+
+/// Writes a random program of the requested number of instructions, generated from the seed, as
+/// a JSON instruction file.
+fn generate_program() -> Result<(), Box<dyn Error>> {
+    let count_arg = argument(3)?;
+    let instruction_count = count_arg
+        .parse::<usize>()
+        .map_err(|_| format!("Invalid instruction count: {}", count_arg))?;
+    let seed = match flag_value(SEED_FLAG) {
+        Some(seed) => seed
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid seed: {}", seed))?,
+        None => 0,
+    };
+    let program = generator::generate_program(instruction_count, seed);
+    write_output(&serde_json::to_string_pretty(&program)?)
+}
+
+/// Builds the processor every program starts from, with its memory initialized from the image
+/// following the memory initialization flag, if given, and its registers seeded by the register
+/// flags.
+fn initial_state() -> Result<Processor, Box<dyn Error>> {
+    let mut processor = Processor::with_config(ProcessorConfig::default());
+    if let Some(image_path) = flag_value(MEM_INIT_FLAG) {
+        let image = fs::read(resolve_path(&image_path)?)?;
+        processor.load_memory(&export::memory_from_le_bytes(&image)?)?;
+    }
+    for seed in flag_values(REG_FLAG) {
+        let (register, value) = cpusim::parse_register_seed(&seed)?;
+        processor.seed_register(register, value)?;
+    }
+    Ok(processor)
+}
+
+/// Writes the data memory of the final state of the program to a binary image.
+fn dump_memory(logs: &[(String, Vec<Processor>)], dump_path: &str) -> Result<(), Box<dyn Error>> {
+    let [(_, state_log)] = logs else {
+        return Err("Only the memory of a single input program can be dumped".into());
+    };
+    if let Some(final_state) = state_log.last() {
+        fs::write(resolve_path(dump_path)?, export::memory_to_le_bytes(final_state.memory()))?;
+    }
+    Ok(())
+}
+
+/// Prints the requested traces, a warning if the log is truncated, and the statistics of a run.
+fn report_run(instructions: &[Instruction], state_log: &[Processor], max_cycles: usize) {
+    let Some(processor) = state_log.last() else {
+        return;
+    };
+    let trace = has_flag(TRACE_FLAG);
+    let trace_forwarding = has_flag(TRACE_FORWARDING_FLAG);
+
+    // The initial state has no trace, the simulated cycles start at 1
+    for (cycle, state) in state_log.iter().enumerate().skip(1) {
+        if trace {
+            eprintln!("{}", state.trace_line(cycle));
+        }
+        if trace_forwarding {
+            eprintln!("{}", state.forwarding_line(cycle));
+        }
+    }
+
+    if is_truncated(instructions, state_log) {
+        eprintln!(
+            "Warning: simulation stopped after {} cycles before the program completed, the log is truncated",
+            max_cycles
+        );
+        eprintln!("{}", processor.pending_report());
+    }
+
+    let statistics = Statistics::new(processor.cycle(), processor.committed_instructions());
+    eprintln!("{}", statistics);
+    eprintln!("{}", processor.stall_report());
+    eprintln!("{}", processor.instruction_mix());
+    eprintln!("{}", processor.queue_waits());
+    eprintln!("{}", processor.peak_occupancy());
 }
 
-fn save_log(state_log: &Vec<architecture::Processor>) -> Result<(), Box<dyn Error>> {
-    let output_file = resolve_output_path()?;
-    match serde_json::to_string_pretty(state_log) {
-        Ok(json) => fs::write(output_file.as_path(), json)?,
+/// Checks if the cycle cap stopped the simulation before the program completed. Stopping at an
+/// idle cycle as requested by the idle flag is not a truncation.
+fn is_truncated(instructions: &[Instruction], state_log: &[Processor]) -> bool {
+    let stopped_when_idle = |state: &Processor| has_flag(LOG_UNTIL_IDLE_FLAG) && state.is_fully_idle();
+    state_log
+        .last()
+        .is_some_and(|state| !state.has_completed(instructions) && !stopped_when_idle(state))
+}
+
+/// Reads and parses every program of the input argument, named after their input path.
+/// Several input files separated by commas are simulated back to back.
+/// Fails if a program exceeds the maximum program size.
+fn parse_inputs() -> Result<Vec<NamedProgram>, Box<dyn Error>> {
+    let max_program_size = parse_max_program_size()?;
+    let mut programs: Vec<NamedProgram> = Vec::new();
+    for input_path in argument(1)?.split(INPUT_SEPARATOR) {
+        if programs.iter().any(|(name, _)| name == input_path) {
+            return Err(format!("Input program {} is given several times", input_path).into());
+        }
+        let instructions = parse_input(input_path)?;
+        cpusim::check_program_size(&instructions, max_program_size)
+            .map_err(|error| format!("{}: {}", input_path, error))?;
+        programs.push((input_path.to_string(), instructions));
+    }
+    Ok(programs)
+}
+
+fn parse_input(input_path: &str) -> Result<Vec<Instruction>, Box<dyn Error>> {
+    let input = read_input(input_path)?;
+    if is_assembly_input(input_path) {
+        return Ok(cpusim::parse_assembly(&input)?);
+    }
+    Ok(cpusim::parse_instructions_from_json(&input)?)
+}
+
+/// Checks if the input is assembly text, either through the flag or a `.s` input file.
+fn is_assembly_input(input_path: &str) -> bool {
+    has_flag(ASM_FLAG)
+        || Path::new(input_path)
+            .extension()
+            .is_some_and(|extension| extension == ASM_EXTENSION)
+}
+
+/// Decodes every instruction up front, reporting each one that is invalid with its index.
+/// Fails if any instruction cannot be decoded.
+fn check_program(instructions: &[Instruction]) -> Result<(), Box<dyn Error>> {
+    let mut invalid_instructions = 0;
+    for (index, instruction) in instructions.iter().enumerate() {
+        if let Err(error) = instruction.decode(index as u64) {
+            eprintln!("{} at index {}: {}", error, index, instruction.text());
+            invalid_instructions += 1;
+        }
+    }
+
+    if invalid_instructions > 0 {
+        return Err(format!(
+            "{} of {} instructions could not be decoded",
+            invalid_instructions,
+            instructions.len()
+        )
+        .into());
+    }
+    eprintln!("All {} instructions decoded successfully", instructions.len());
+    Ok(())
+}
+
+/// Reads the optional cycle cap from the third argument, defaulting to `MAX_CYCLES`.
+fn parse_max_cycles() -> Result<usize, Box<dyn Error>> {
+    match optional_argument(3) {
+        Some(arg) => Ok(arg
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid maximum cycle count: {}", arg))?),
+        None => Ok(MAX_CYCLES),
+    }
+}
+
+/// Reads the maximum program size following its flag, defaulting to `MAX_PROGRAM_SIZE`.
+fn parse_max_program_size() -> Result<usize, Box<dyn Error>> {
+    match flag_value(MAX_PROGRAM_SIZE_FLAG) {
+        Some(arg) => Ok(arg
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid maximum program size: {}", arg))?),
+        None => Ok(MAX_PROGRAM_SIZE),
+    }
+}
+
+/// Reads the log format following the format flag, defaulting to JSON. The verbose flag only
+/// extends the JSON log, and is rejected with the ndjson log.
+fn parse_format() -> Result<LogFormat, Box<dyn Error>> {
+    match flag_value(FORMAT_FLAG).as_deref() {
+        None | Some("json") if has_flag(VERBOSE_FLAG) => Ok(LogFormat::VerboseJson),
+        None | Some("json") => Ok(LogFormat::Json),
+        Some("csv") => Ok(LogFormat::Csv),
+        Some("diff") => Ok(LogFormat::Diff),
+        Some("msgpack") => Ok(LogFormat::MessagePack),
+        Some("ndjson") if has_flag(VERBOSE_FLAG) => {
+            Err(format!("{} cannot be combined with the ndjson log format", VERBOSE_FLAG).into())
+        }
+        Some("ndjson") => Ok(LogFormat::Ndjson),
+        Some(format) => Err(format!("Unknown log format: {}", format).into()),
+    }
+}
+
+/// Reads the program from the input file, or from stdin when the input path is `-`.
+fn read_input(input_path: &str) -> Result<String, Box<dyn Error>> {
+    if input_path == STDIO_PATH {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        let input_file = resolve_path(input_path)?;
+        Ok(fs::read_to_string(input_file.as_path())?)
+    }
+}
+
+fn save_log(
+    state_log: &[Processor],
+    format: &LogFormat,
+    versioned: bool,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        LogFormat::Json if !versioned => write_output(&export::log_to_json(state_log)?)?,
+        LogFormat::Json => save_json(state_log, versioned)?,
+        LogFormat::VerboseJson => save_json(&export::verbose_log(state_log), versioned)?,
+        LogFormat::Csv => {
+            let mut csv = Vec::new();
+            export::write_csv(state_log, &mut csv)?;
+            write_output(String::from_utf8(csv)?.trim_end())?;
+        }
+        LogFormat::Diff => {
+            let mut diff = Vec::new();
+            export::write_diff(state_log, &mut diff)?;
+            write_output(&String::from_utf8(diff)?)?;
+        }
+        LogFormat::MessagePack => save_msgpack(state_log, versioned)?,
+        LogFormat::Ndjson => {
+            let mut ndjson = Vec::new();
+            for state in state_log {
+                export::write_ndjson_state(state, &mut ndjson)?;
+            }
+            write_output(String::from_utf8(ndjson)?.trim_end())?;
+        }
+    }
+    Ok(())
+}
+
+/// Saves the JSON log followed by the architectural register values of the final state.
+fn save_log_with_final_registers(
+    state_log: &[Processor],
+    format: &LogFormat,
+    versioned: bool,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        LogFormat::Json => save_json(&export::LogWithFinalRegisters::new(state_log), versioned),
+        _ => Err("Only the JSON log can hold the final registers".into()),
+    }
+}
+
+/// Saves the logs of several programs as a single JSON object keyed by program name.
+fn save_combined_log(
+    logs: &[(String, Vec<Processor>)],
+    format: &LogFormat,
+    versioned: bool,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        LogFormat::Json => {
+            let combined: BTreeMap<&str, &[Processor]> = logs
+                .iter()
+                .map(|(name, state_log)| (name.as_str(), state_log.as_slice()))
+                .collect();
+            save_json(&combined, versioned)
+        }
+        LogFormat::MessagePack => {
+            let combined: BTreeMap<&str, &[Processor]> = logs
+                .iter()
+                .map(|(name, state_log)| (name.as_str(), state_log.as_slice()))
+                .collect();
+            save_msgpack(&combined, versioned)
+        }
+        LogFormat::VerboseJson => {
+            let combined: BTreeMap<&str, Vec<export::VerboseState>> = logs
+                .iter()
+                .map(|(name, state_log)| (name.as_str(), export::verbose_log(state_log)))
+                .collect();
+            save_json(&combined, versioned)
+        }
+        LogFormat::Csv | LogFormat::Diff | LogFormat::Ndjson => {
+            Err("Only the JSON log supports several input programs".into())
+        }
+    }
+}
+
+/// Serializes the logged cycles to JSON, wrapped with the log format version if `versioned`.
+fn save_json<T: Serialize + ?Sized>(cycles: &T, versioned: bool) -> Result<(), Box<dyn Error>> {
+    let json = if versioned {
+        serde_json::to_string_pretty(&export::VersionedLog::new(cycles))
+    } else {
+        serde_json::to_string_pretty(cycles)
+    };
+    match json {
+        Ok(json) => write_output(&json)?,
         Err(e) => eprintln!("Error serializing processor state: {}", e),
     }
     Ok(())
 }
 
-fn resolve_path(arg_index: usize) -> Result<PathBuf, Box<dyn Error>> {
-    let mut path = PathBuf::from(env::current_dir()?);
-    // Navigate up two directories to get to `cs470`
-    path.pop(); // Move up from `src` to `cpusim`
-    path.pop(); // Move up from `cpusim` to `cs470`
+/// Serializes the logged cycles to MessagePack, wrapped with the log format version if
+/// `versioned`.
+fn save_msgpack<T: Serialize + ?Sized>(cycles: &T, versioned: bool) -> Result<(), Box<dyn Error>> {
+    let mut msgpack = Vec::new();
+    if versioned {
+        export::write_msgpack(&export::VersionedLog::new(cycles), &mut msgpack)?;
+    } else {
+        export::write_msgpack(cycles, &mut msgpack)?;
+    }
+    if argument(2)? == STDIO_PATH {
+        io::stdout().lock().write_all(&msgpack)?;
+    } else {
+        fs::write(resolve_output_path()?, msgpack)?;
+    }
+    Ok(())
+}
 
-    // Retrieve the argument at `arg_index` and append it to the path
-    let arg = env::args()
-        .nth(arg_index)
-        .ok_or("Expected argument not found")?;
-    path.push(arg);
-    Ok(path)
+/// Writes the serialized log to the output file, or to stdout when the output argument is `-`.
+fn write_output(log: &str) -> Result<(), Box<dyn Error>> {
+    if argument(2)? == STDIO_PATH {
+        writeln!(io::stdout().lock(), "{}", log)?;
+    } else {
+        let output_file = resolve_output_path()?;
+        fs::write(output_file.as_path(), log)?;
+    }
+    Ok(())
+}
+
+/// Returns the positional argument at `arg_index`, ignoring `--` flags and their values.
+fn optional_argument(arg_index: usize) -> Option<String> {
+    let mut args = env::args();
+    let mut positional_args = Vec::new();
+    while let Some(arg) = args.next() {
+        if VALUE_FLAGS.contains(&arg.as_str()) {
+            args.next(); // Skip the value of the flag
+        } else if !arg.starts_with("--") {
+            positional_args.push(arg);
+        }
+    }
+    positional_args.into_iter().nth(arg_index)
+}
+
+fn argument(arg_index: usize) -> Result<String, Box<dyn Error>> {
+    Ok(optional_argument(arg_index).ok_or("Expected argument not found")?)
+}
+
+fn has_flag(flag: &str) -> bool {
+    env::args().any(|arg| arg == flag)
+}
+
+/// Returns the argument following `flag`, if the flag is given.
+fn flag_value(flag: &str) -> Option<String> {
+    env::args().skip_while(|arg| arg != flag).nth(1)
 }
 
-fn resolve_input_path() -> Result<PathBuf, Box<dyn Error>> {
-    resolve_path(1)
+/// Returns the argument following every occurrence of `flag`, in order.
+fn flag_values(flag: &str) -> Vec<String> {
+    let args: Vec<String> = env::args().collect();
+    args.windows(2)
+        .filter(|pair| pair[0] == flag)
+        .map(|pair| pair[1].clone())
+        .collect()
+}
+
+fn resolve_path(path_argument: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let mut path = env::current_dir()?;
+    // Append the path argument to the current directory
+    path.push(path_argument);
+    Ok(path)
 }
 
 fn resolve_output_path() -> Result<PathBuf, Box<dyn Error>> {
-    resolve_path(2)
+    resolve_path(&argument(2)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instructions(program: &[&str]) -> Vec<Instruction> {
+        program
+            .iter()
+            .map(|instruction| Instruction::new(instruction.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn check_accepts_a_program_that_decodes() {
+        let program = instructions(&["addi x1, x0, 1", "mulu x2, x1, x1", "nop"]);
+        assert!(check_program(&program).is_ok());
+    }
+
+    #[test]
+    fn check_counts_every_invalid_instruction() {
+        let program = instructions(&["addi x1, x0, 1", "foo x1, x2, x3", "add x1, x2"]);
+
+        let error = check_program(&program).unwrap_err();
+
+        assert_eq!(error.to_string(), "2 of 3 instructions could not be decoded");
+    }
 }
@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::Serialize;
+
+/// Summary of the simulated performance of a program.
+#[derive(Clone, Serialize)]
+pub struct Statistics {
+    #[serde(rename = "Cycles")]
+    pub cycles: u64,
+    #[serde(rename = "CommittedInstructions")]
+    pub committed_instructions: u64,
+    #[serde(rename = "IPC")]
+    pub ipc: f64,
+}
+
+impl Statistics {
+    pub fn new(cycles: u64, committed_instructions: u64) -> Statistics {
+        let ipc = if cycles == 0 {
+            0.0
+        } else {
+            committed_instructions as f64 / cycles as f64
+        };
+        Statistics {
+            cycles,
+            committed_instructions,
+            ipc,
+        }
+    }
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Cycles: {}, Committed instructions: {}, IPC: {:.3}",
+            self.cycles, self.committed_instructions, self.ipc
+        )
+    }
+}
+
+/// Number of cycles in which backpressure was applied, broken down by the exhausted resource.
+/// A cycle in which several resources are exhausted is counted for each of them.
+#[derive(Clone, Copy, Default, Serialize)]
+pub struct StallReport {
+    #[serde(rename = "FreeList")]
+    pub free_list: u64,
+    #[serde(rename = "ActiveList")]
+    pub active_list: u64,
+    #[serde(rename = "IntegerQueue")]
+    pub integer_queue: u64,
+    #[serde(rename = "ReadPorts")]
+    pub read_ports: u64,
+}
+
+impl fmt::Display for StallReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Stall cycles: free list {}, active list {}, integer queue {}, read ports {}",
+            self.free_list, self.active_list, self.integer_queue, self.read_ports
+        )
+    }
+}
+
+/// Number of entries held by the main structures of the processor at the end of a cycle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Occupancy {
+    #[serde(rename = "ActiveList")]
+    pub active_list: usize,
+    #[serde(rename = "IntegerQueue")]
+    pub integer_queue: usize,
+    /// ALUs whose first stage is occupied, which cannot accept an instruction in the next cycle.
+    #[serde(rename = "BusyALUs")]
+    pub busy_alus: usize,
+    #[serde(rename = "FreeList")]
+    pub free_list: usize,
+}
+
+impl fmt::Display for Occupancy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Occupancy: active list {}, integer queue {}, busy ALUs {}, free list {}",
+            self.active_list, self.integer_queue, self.busy_alus, self.free_list
+        )
+    }
+}
+
+/// Largest number of entries the active list and the integer queue held over a run, to size
+/// them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct PeakOccupancy {
+    #[serde(rename = "ActiveList")]
+    pub max_active_list_len: usize,
+    #[serde(rename = "IntegerQueue")]
+    pub max_integer_queue_len: usize,
+}
+
+impl PeakOccupancy {
+    /// Raises the high-water marks to the given lengths, if they are larger.
+    pub fn record(&mut self, active_list_len: usize, integer_queue_len: usize) {
+        self.max_active_list_len = self.max_active_list_len.max(active_list_len);
+        self.max_integer_queue_len = self.max_integer_queue_len.max(integer_queue_len);
+    }
+}
+
+impl fmt::Display for PeakOccupancy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Peak occupancy: active list {}, integer queue {}",
+            self.max_active_list_len, self.max_integer_queue_len
+        )
+    }
+}
+
+/// Stage an instruction fetched but not retired yet is stuck in.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum PendingStatus {
+    /// Decoded, waiting to be renamed and dispatched.
+    Decoded,
+    /// In the integer queue, waiting for its operands or a free ALU.
+    Queued,
+    /// Issued to an ALU, its result not produced yet.
+    Executing,
+    /// Done, waiting for older instructions to retire.
+    Done,
+    /// Raised an exception, waiting for the rollback.
+    Exception,
+    /// Younger than an exception, waiting to be rolled back.
+    Squashed,
+}
+
+impl fmt::Display for PendingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self {
+            PendingStatus::Decoded => "decoded, waiting for dispatch",
+            PendingStatus::Queued => "waiting in the integer queue",
+            PendingStatus::Executing => "executing",
+            PendingStatus::Done => "done but not committed",
+            PendingStatus::Exception => "raised an exception",
+            PendingStatus::Squashed => "waiting to be rolled back",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// Instructions fetched but not retired, oldest first, with the stage each is in.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct PendingReport {
+    pub instructions: Vec<(u64, PendingStatus)>,
+}
+
+impl PendingReport {
+    /// Returns the PC of every pending instruction, oldest first.
+    pub fn pcs(&self) -> Vec<u64> {
+        self.instructions.iter().map(|(pc, _)| *pc).collect()
+    }
+}
+
+impl fmt::Display for PendingReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.instructions.is_empty() {
+            return write!(f, "Pending instructions: none");
+        }
+        let instructions: Vec<String> = self
+            .instructions
+            .iter()
+            .map(|(pc, status)| format!("PC {} ({})", pc, status))
+            .collect();
+        write!(f, "Pending instructions: {}", instructions.join(", "))
+    }
+}
+
+/// Number of committed instructions of each op code.
+#[derive(Clone, Default, Serialize)]
+pub struct InstructionMix {
+    pub counts: HashMap<String, usize>,
+}
+
+impl InstructionMix {
+    /// Counts one more committed instruction of the given op code.
+    pub fn record(&mut self, op_code: &str) {
+        *self.counts.entry(op_code.to_string()).or_insert(0) += 1;
+    }
+
+    /// Returns the number of committed instructions of the given op code.
+    pub fn count(&self, op_code: &str) -> usize {
+        self.counts.get(op_code).copied().unwrap_or(0)
+    }
+}
+
+impl fmt::Display for InstructionMix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Sort by op code so the histogram reads the same on every run
+        let mut counts: Vec<(&String, &usize)> = self.counts.iter().collect();
+        counts.sort();
+        let counts: Vec<String> = counts
+            .iter()
+            .map(|(op_code, count)| format!("{} {}", op_code, count))
+            .collect();
+        write!(f, "Instruction mix: {}", counts.join(", "))
+    }
+}
+
+/// Number of issued instructions by the number of cycles they waited in the integer queue,
+/// from dispatch to issue.
+#[derive(Clone, Default, Serialize)]
+pub struct QueueWaitHistogram {
+    pub counts: BTreeMap<u64, usize>,
+}
+
+impl QueueWaitHistogram {
+    /// Counts one more instruction issued after waiting the given number of cycles.
+    pub fn record(&mut self, wait_cycles: u64) {
+        *self.counts.entry(wait_cycles).or_insert(0) += 1;
+    }
+
+    /// Returns the number of instructions issued after waiting the given number of cycles.
+    pub fn count(&self, wait_cycles: u64) -> usize {
+        self.counts.get(&wait_cycles).copied().unwrap_or(0)
+    }
+}
+
+impl fmt::Display for QueueWaitHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts: Vec<String> = self
+            .counts
+            .iter()
+            .map(|(wait_cycles, count)| format!("{} cycles {}", wait_cycles, count))
+            .collect();
+        write!(f, "Integer queue waits: {}", counts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipc_is_the_committed_instructions_per_cycle() {
+        let statistics = Statistics::new(8, 6);
+        assert_eq!(statistics.ipc, 0.75);
+        assert_eq!(
+            statistics.to_string(),
+            "Cycles: 8, Committed instructions: 6, IPC: 0.750"
+        );
+    }
+
+    #[test]
+    fn ipc_of_a_run_without_cycles_is_zero() {
+        assert_eq!(Statistics::new(0, 0).ipc, 0.0);
+    }
+}
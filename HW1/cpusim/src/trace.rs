@@ -0,0 +1,117 @@
+use std::fmt;
+
+/// Value put by an ALU on its forwarding path and picked up by a waiting integer queue entry.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ForwardEvent {
+    pub alu_index: usize,
+    /// Physical register the value is written to.
+    pub register: u8,
+    pub value: u64,
+    /// PC of the instruction producing the value.
+    pub producer_pc: u64,
+    /// PC of the integer queue entry receiving the value as an operand.
+    pub consumer_pc: u64,
+}
+
+impl fmt::Display for ForwardEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ALU{} p{}={} {}->{}",
+            self.alu_index, self.register, self.value, self.producer_pc, self.consumer_pc
+        )
+    }
+}
+
+/// Events of a single cycle, identified by the PCs of the instructions involved.
+#[derive(Clone, Default)]
+pub struct CycleTrace {
+    pub decoded: Vec<u64>,
+    pub dispatched: Vec<u64>,
+    /// PC of each issued instruction with the index of the ALU it was issued to.
+    pub issued: Vec<(u64, usize)>,
+    pub committed: Vec<u64>,
+    pub rolled_back: Vec<u64>,
+    pub exception: Option<u64>,
+    /// Target of a retired taken branch.
+    pub redirect: Option<u64>,
+    /// Values forwarded from the ALUs to the integer queue.
+    pub forwarded: Vec<ForwardEvent>,
+}
+
+impl CycleTrace {
+    pub fn clear(&mut self) {
+        self.decoded.clear();
+        self.dispatched.clear();
+        self.issued.clear();
+        self.committed.clear();
+        self.rolled_back.clear();
+        self.exception = None;
+        self.redirect = None;
+        self.forwarded.clear();
+    }
+
+    /// Checks if no instruction moved through the pipeline in the cycle.
+    pub fn is_idle(&self) -> bool {
+        self.decoded.is_empty()
+            && self.dispatched.is_empty()
+            && self.issued.is_empty()
+            && self.committed.is_empty()
+            && self.rolled_back.is_empty()
+            && self.exception.is_none()
+            && self.redirect.is_none()
+            && self.forwarded.is_empty()
+    }
+
+    /// Describes the forwarded values, kept apart from the pipeline events as they are numerous.
+    pub fn forwarding_summary(&self) -> String {
+        if self.forwarded.is_empty() {
+            return "-".to_string();
+        }
+        self.forwarded
+            .iter()
+            .map(|event| event.to_string())
+            .collect::<Vec<String>>()
+            .join(" | ")
+    }
+}
+
+fn format_pcs(pcs: &[u64]) -> String {
+    if pcs.is_empty() {
+        return "-".to_string();
+    }
+    pcs.iter()
+        .map(|pc| pc.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+impl fmt::Display for CycleTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let issued = if self.issued.is_empty() {
+            "-".to_string()
+        } else {
+            self.issued
+                .iter()
+                .map(|(pc, alu_index)| format!("{}->ALU{}", pc, alu_index))
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+        write!(
+            f,
+            "fetch/decode: {} | dispatch: {} | issue: {} | commit: {} | rollback: {}",
+            format_pcs(&self.decoded),
+            format_pcs(&self.dispatched),
+            issued,
+            format_pcs(&self.committed),
+            format_pcs(&self.rolled_back)
+        )?;
+        if let Some(pc) = self.exception {
+            write!(f, " | exception: {}", pc)?;
+        }
+        if let Some(pc) = self.redirect {
+            write!(f, " | redirect: {}", pc)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use cpusim::architecture::{Processor, ProcessorConfig};
+
+fn run(program: &[&str], alu_count: usize) -> Vec<Processor> {
+    let config = ProcessorConfig {
+        alu_count,
+        mulu_latency: 3,
+        ..ProcessorConfig::default()
+    };
+    common::run(program, config)
+}
+
+#[test]
+fn lowest_ready_pc_issues_first() {
+    // Operands become ready out of program order: the dependent instructions at PCs 1, 4 and 6 are
+    // late, and the instructions around them are ready as soon as they are dispatched.
+    let program = [
+        "addi x1, x0, 2",
+        "mulu x2, x1, x1",
+        "addi x3, x0, 1",
+        "addi x4, x0, 5",
+        "add x5, x2, x3",
+        "addi x6, x0, 7",
+        "add x7, x6, x4",
+        "addi x8, x0, 9",
+    ];
+    for alu_count in [1, 2] {
+        let state_log = run(&program, alu_count);
+        let mut issue_order = Vec::new();
+        for state in &state_log {
+            let issued: Vec<u64> = state.issued_instructions().iter().map(|&(pc, _)| pc).collect();
+            // Within a cycle, older instructions go to the lower ALUs
+            assert!(issued.windows(2).all(|pcs| pcs[0] < pcs[1]));
+            // Every ready instruction left waiting is younger than the ones issued
+            if let Some(&youngest_issued) = issued.last() {
+                assert!(state
+                    .integer_queue()
+                    .iter()
+                    .filter(|entry| entry.is_ready())
+                    .all(|entry| entry.pc > youngest_issued));
+            }
+            issue_order.extend(issued);
+        }
+        issue_order.sort();
+        assert_eq!(issue_order, (0..program.len() as u64).collect::<Vec<u64>>());
+    }
+
+    // With a single ALU, instructions waiting on a value are overtaken by younger ready ones
+    let issue_order: Vec<u64> = run(&program, 1)
+        .iter()
+        .flat_map(|state| state.issued_instructions().iter().map(|&(pc, _)| pc))
+        .collect();
+    assert_eq!(issue_order, vec![0, 2, 1, 3, 4, 5, 7, 6]);
+}
@@ -0,0 +1,62 @@
+use std::fs;
+use std::path::PathBuf;
+
+use cpusim::architecture::{Processor, ProcessorConfig};
+use cpusim::run_program;
+use serde_json::Value;
+
+fn run(program: Vec<String>, alu_stages: usize) -> Vec<Processor> {
+    let config = ProcessorConfig {
+        alu_stages,
+        ..ProcessorConfig::default()
+    };
+    run_program(program, config).unwrap()
+}
+
+/// Returns the first logged cycle satisfying the condition on any ALU.
+fn first_cycle(state_log: &[Processor], condition: fn(&cpusim::arch_modules::ALU) -> bool) -> usize {
+    state_log
+        .iter()
+        .position(|state| state.alus().iter().any(condition))
+        .unwrap()
+}
+
+#[test]
+fn result_forwards_one_cycle_per_extra_stage() {
+    for alu_stages in [2, 4] {
+        let state_log = run(vec!["addi x1, x0, 1".to_string()], alu_stages);
+        let issue_cycle = first_cycle(&state_log, |alu| alu.is_busy());
+        let forward_cycle = first_cycle(&state_log, |alu| alu.is_forwarding);
+        assert_eq!(forward_cycle - issue_cycle, alu_stages - 1);
+        assert!(state_log.last().unwrap().is_done());
+    }
+}
+
+#[test]
+fn default_depth_matches_the_reference_log() {
+    let test_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../given_tests/01");
+    let input = fs::read_to_string(test_dir.join("input.json")).unwrap();
+    let reference: Vec<Value> =
+        serde_json::from_str(&fs::read_to_string(test_dir.join("output.json")).unwrap()).unwrap();
+
+    let state_log = run(serde_json::from_str(&input).unwrap(), 2);
+    assert_eq!(state_log.len(), reference.len());
+    for (state, expected) in state_log.iter().zip(&reference) {
+        assert!(matches_reference(&serde_json::to_value(state).unwrap(), expected));
+    }
+}
+
+/// Compares a logged value to the reference. The log may hold more fields than the reference,
+/// only the fields of the reference are compared.
+fn matches_reference(logged: &Value, reference: &Value) -> bool {
+    match (logged, reference) {
+        (Value::Object(logged), Value::Object(reference)) => reference
+            .iter()
+            .all(|(field, value)| logged.get(field).is_some_and(|x| matches_reference(x, value))),
+        (Value::Array(logged), Value::Array(reference)) => {
+            logged.len() == reference.len()
+                && logged.iter().zip(reference).all(|(x, y)| matches_reference(x, y))
+        }
+        _ => logged == reference,
+    }
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+
+#[test]
+fn bitwise_operations_commit_their_results() {
+    let state_log = run(
+        &[
+            "addi x1, x0, 12",
+            "addi x2, x0, 10",
+            "addi x3, x0, -1",
+            "addi x4, x0, 255",
+            "or x5, x1, x2",
+            "and x6, x1, x2",
+            "xor x7, x1, x2",
+            "andi x8, x3, 240",
+            "ori x9, x1, 3",
+            "xori x10, x4, -1",
+            "xor x11, x3, x3",
+            "and x12, x4, x3",
+        ],
+        ProcessorConfig::default(),
+    );
+    let processor = state_log.last().unwrap();
+
+    // The committed OR is read from the physical register mapped to x5
+    let or_register = processor.register_map_table()[5] as usize;
+    assert_eq!(processor.physical_register_file()[or_register], 14);
+    // Immediates are sign-extended before the operation, so -1 flips every bit
+    assert_eq!(processor.architectural_registers()[5..13], [14, 8, 6, 240, 15, !255, 0, 255]);
+}
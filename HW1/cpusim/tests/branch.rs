@@ -0,0 +1,77 @@
+mod common;
+
+use cpusim::architecture::{Processor, ProcessorConfig};
+
+/// Runs the program, returning the log and the PC of every retired instruction in order.
+fn run(program: &[&str]) -> (Vec<Processor>, Vec<u64>) {
+    let state_log = common::run(program, ProcessorConfig::default());
+    let retired = state_log
+        .iter()
+        .flat_map(|state| state.retired_instructions().to_vec())
+        .collect();
+    (state_log, retired)
+}
+
+#[test]
+fn taken_beq_skips_the_instruction_before_its_target() {
+    let program = [
+        "addi x1, x0, 1",
+        "beq x1, x1, 3",
+        "addi x2, x0, 5",
+        "addi x3, x0, 7",
+    ];
+
+    let (state_log, retired) = run(&program);
+
+    assert_eq!(retired, [0, 1, 3]);
+    // The skipped instruction was fetched on the wrong path and squashed when the branch retired
+    let rolled_back: Vec<u64> = state_log
+        .iter()
+        .flat_map(|state| state.rolled_back_instructions().to_vec())
+        .collect();
+    assert!(rolled_back.contains(&2));
+    let registers = state_log.last().unwrap().architectural_registers();
+    assert_eq!(registers[1..4], [1, 0, 7]);
+}
+
+#[test]
+fn not_taken_bne_falls_through() {
+    let program = [
+        "addi x1, x0, 1",
+        "bne x1, x1, 3",
+        "addi x2, x0, 5",
+        "addi x3, x0, 7",
+    ];
+
+    let (state_log, retired) = run(&program);
+
+    assert_eq!(retired, [0, 1, 2, 3]);
+    assert!(state_log
+        .iter()
+        .all(|state| state.rolled_back_instructions().is_empty()));
+    let registers = state_log.last().unwrap().architectural_registers();
+    assert_eq!(registers[1..4], [1, 5, 7]);
+}
+
+#[test]
+fn taken_branch_squashes_the_wrong_path_when_it_retires() {
+    let program = [
+        "addi x1, x0, 1",
+        "bne x1, x0, 3",
+        "addi x2, x0, 5",
+        "addi x3, x0, 7",
+    ];
+
+    let (state_log, retired) = run(&program);
+
+    let retire_cycle = state_log
+        .iter()
+        .position(|state| state.retired_instructions().contains(&1))
+        .unwrap();
+    // Both younger instructions were fetched on the wrong path, the target is fetched again
+    assert_eq!(state_log[retire_cycle].rolled_back_instructions(), [3, 2]);
+    assert!(state_log[..retire_cycle]
+        .iter()
+        .all(|state| state.rolled_back_instructions().is_empty()));
+    assert_eq!(retired, [0, 1, 3]);
+}
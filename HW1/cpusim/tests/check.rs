@@ -0,0 +1,32 @@
+mod common;
+
+use common::{run_simulator, TestDirectory};
+
+#[test]
+fn check_reports_the_invalid_instruction_and_fails() {
+    let directory = TestDirectory::new("check-invalid");
+    let input = directory.write_program("program.json", &["addi x1, x0, 1", "foo x1, x2, x3"]);
+    let output = directory.join("output.json");
+
+    let run = run_simulator(&input, &output, &["--check"]);
+
+    assert!(!run.status.success());
+    let stderr = String::from_utf8(run.stderr).unwrap();
+    assert!(stderr.contains("Invalid op code at index 1: foo x1, x2, x3"), "{}", stderr);
+    // Nothing is simulated
+    assert!(!output.exists());
+}
+
+#[test]
+fn check_of_a_valid_program_succeeds_without_simulating() {
+    let directory = TestDirectory::new("check-valid");
+    let input = directory.write_program("program.json", &["addi x1, x0, 1", "mulu x2, x1, x1"]);
+    let output = directory.join("output.json");
+
+    let run = run_simulator(&input, &output, &["--check"]);
+
+    assert!(run.status.success());
+    let stderr = String::from_utf8(run.stderr).unwrap();
+    assert!(stderr.contains("All 2 instructions decoded successfully"), "{}", stderr);
+    assert!(!output.exists());
+}
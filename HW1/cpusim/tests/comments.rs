@@ -0,0 +1,52 @@
+use cpusim::architecture::ProcessorConfig;
+use cpusim::{parse_instructions_from_json, run, MAX_CYCLES};
+
+const PROGRAM: &str = r##"[
+    "# Computes x3 = x1 * x1 - x1",
+    "addi x1, x0, 3",
+    "",
+    "   ",
+    "// square",
+    "mulu x2, x1, x1",
+    "  # indented comment",
+    "sub x3, x2, x1"
+]"##;
+
+#[test]
+fn comments_and_blank_entries_take_no_pc() {
+    let instructions = parse_instructions_from_json(PROGRAM).unwrap();
+
+    let texts: Vec<&str> = instructions.iter().map(|x| x.text()).collect();
+    assert_eq!(texts, ["addi x1, x0, 3", "mulu x2, x1, x1", "sub x3, x2, x1"]);
+}
+
+#[test]
+fn instructions_around_comments_get_contiguous_pcs() {
+    let instructions = parse_instructions_from_json(PROGRAM).unwrap();
+
+    let state_log = run(&instructions, ProcessorConfig::default(), MAX_CYCLES).unwrap();
+
+    let retired: Vec<u64> = state_log
+        .iter()
+        .flat_map(|state| state.retired_instructions().to_vec())
+        .collect();
+    assert_eq!(retired, [0, 1, 2]);
+    let active_list_pcs: Vec<u64> = state_log
+        .iter()
+        .flat_map(|state| state.active_list().iter().map(|entry| entry.pc))
+        .collect();
+    assert!(active_list_pcs.iter().all(|pc| *pc < 3));
+    assert_eq!(state_log.last().unwrap().architectural_registers()[3], 6);
+}
+
+#[test]
+fn branch_target_counts_only_instructions() {
+    // Target 3 is the fourth instruction, whatever the comments before it
+    let json = r##"["addi x1, x0, 1", "# skip the next one", "beq x0, x0, 3", "addi x1, x0, 5",
+        "", "addi x2, x0, 7"]"##;
+    let instructions = parse_instructions_from_json(json).unwrap();
+
+    let state_log = run(&instructions, ProcessorConfig::default(), MAX_CYCLES).unwrap();
+
+    assert_eq!(state_log.last().unwrap().architectural_registers()[1..3], [1, 7]);
+}
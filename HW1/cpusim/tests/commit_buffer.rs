@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use cpusim::arch_modules::{ActiveListEntry, CommitBufferEntry};
+use cpusim::architecture::{Processor, ProcessorBuilder};
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+/// Runs the program, returning the state log and the first commit buffer entry seen for every PC.
+fn run_with_commit_buffer(
+    program: &[&str],
+    fuse_divmod: bool,
+) -> (Vec<Processor>, HashMap<u64, CommitBufferEntry>) {
+    let processor = ProcessorBuilder::new()
+        .fuse_divmod(fuse_divmod)
+        .build()
+        .unwrap();
+    let instructions = parse_program(&program.iter().map(|x| x.to_string()).collect::<Vec<_>>())
+        .unwrap();
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+    assert!(state_log.last().unwrap().has_completed(&instructions));
+
+    let mut entries = HashMap::new();
+    for state in &state_log {
+        for entry in state.commit_buffer() {
+            entries.entry(entry.pc).or_insert_with(|| entry.clone());
+        }
+    }
+    (state_log, entries)
+}
+
+#[test]
+fn commit_buffer_records_the_operation_of_each_result() {
+    let program = ["addi x1, x0, 6", "addi x2, x0, 7", "mulu x3, x1, x2", "sub x4, x3, x1"];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+
+    let (_, entries) = run_with_commit_buffer(&program, false);
+
+    assert_eq!(entries.len(), program.len());
+    for (pc, instruction) in instructions.iter().enumerate() {
+        let decoded = instruction.decode(pc as u64).unwrap();
+        assert_eq!(entries[&(pc as u64)].op_code, decoded.op_code);
+    }
+    let product = &entries[&2];
+    assert_eq!((product.op_a_value, product.op_b_value, product.value), (6, 7, 42));
+    assert_eq!(product.to_string(), format!("PC 2: mulu 6, 7 -> p{} = 42", product.dest_register));
+    let immediate = &entries[&0];
+    assert_eq!((immediate.op_a_value, immediate.op_b_value, immediate.value), (0, 6, 6));
+}
+
+#[test]
+fn fused_division_records_both_halves() {
+    let program = ["addi x1, x0, 45", "addi x2, x0, 7", "divu x3, x1, x2", "remu x4, x1, x2"];
+
+    let (state_log, entries) = run_with_commit_buffer(&program, true);
+
+    // Both halves are issued together to the ALU executing the fused division
+    assert!(state_log.iter().any(|state| {
+        let issued = state.issued_instructions();
+        issued.len() == 2 && issued[0].0 == 2 && issued[1] == (3, issued[0].1)
+    }));
+    let (quotient, remainder) = (&entries[&2], &entries[&3]);
+    assert_eq!((quotient.op_code.as_str(), quotient.value), ("divu", 6));
+    assert_eq!((remainder.op_code.as_str(), remainder.value), ("remu", 3));
+    assert_eq!((remainder.op_a_value, remainder.op_b_value), (45, 7));
+}
+
+#[test]
+fn committing_an_entry_without_buffered_result_is_an_error() {
+    let mut processor = Processor::new();
+    let entry = ActiveListEntry::new(true, false, 1, 1, 7, true);
+
+    let error = processor.commit_entry(entry).unwrap_err();
+    assert_eq!(error, "No commit buffer entry for the instruction at PC 7");
+}
@@ -0,0 +1,37 @@
+use cpusim::architecture::ProcessorBuilder;
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+#[test]
+fn narrow_commit_retires_at_most_two_instructions_per_cycle() {
+    let processor = ProcessorBuilder::new()
+        .decoded_buffer_size(4)
+        .commit_width(2)
+        .build()
+        .unwrap();
+    let program = [
+        "addi x1, x0, 1",
+        "addi x2, x0, 2",
+        "addi x3, x0, 3",
+        "addi x4, x0, 4",
+        "addi x5, x0, 5",
+        "addi x6, x0, 6",
+    ];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    assert!(state_log.iter().all(|state| state.retired_instructions().len() <= 2));
+    // The four instructions dispatched together complete together, but only two of them retire
+    assert!(state_log.iter().any(|state| {
+        state.retired_instructions().len() == 2
+            && state.active_list().iter().take(2).all(|entry| entry.is_done)
+    }));
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.has_completed(&instructions));
+    assert_eq!(final_state.committed_instructions(), program.len() as u64);
+}
+
+#[test]
+fn commit_width_must_be_positive() {
+    assert!(ProcessorBuilder::new().commit_width(0).build().is_err());
+}
@@ -0,0 +1,72 @@
+//! Fixtures of the tests simulating a program, through the library or the simulator binary.
+// Each test crate only uses some of the fixtures
+#![allow(dead_code)]
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use cpusim::architecture::{Processor, ProcessorConfig};
+
+/// Simulates the program on a processor built from `config`, returning the state log.
+/// Panics if the simulation fails.
+pub fn run(program: &[&str], config: ProcessorConfig) -> Vec<Processor> {
+    let instructions = program.iter().map(|x| x.to_string()).collect();
+    cpusim::run_program(instructions, config).unwrap()
+}
+
+/// Directory holding the files of a test, unique to the test and the process. It is removed with
+/// everything in it once dropped.
+pub struct TestDirectory(PathBuf);
+
+impl TestDirectory {
+    pub fn new(name: &str) -> TestDirectory {
+        let path = std::env::temp_dir().join(format!("cpusim-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        TestDirectory(path)
+    }
+
+    /// Returns the path of the file of the given name in the directory.
+    pub fn join(&self, file_name: &str) -> PathBuf {
+        self.0.join(file_name)
+    }
+
+    /// Writes the program to the JSON input file of the given name, returning its path.
+    pub fn write_program(&self, file_name: &str, program: &[&str]) -> PathBuf {
+        let input = self.join(file_name);
+        fs::write(&input, serde_json::to_string(program).unwrap()).unwrap();
+        input
+    }
+}
+
+impl Drop for TestDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Runs the simulator on the input file, writing the log to the output file, followed by the
+/// given arguments.
+pub fn run_simulator(input: &Path, output: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cpusim"))
+        .arg(input)
+        .arg(output)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Runs the simulator with the given arguments, writing `stdin` to its standard input.
+pub fn run_simulator_with_stdin(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cpusim"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Dropping the handle closes the stream, so the simulator reads to its end
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
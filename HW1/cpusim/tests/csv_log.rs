@@ -0,0 +1,37 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+use cpusim::export::write_csv;
+
+#[test]
+fn csv_log_has_a_header_and_one_row_per_cycle() {
+    let program = ["addi x1, x0, 1", "divu x2, x1, x0", "addi x3, x0, 2"];
+    let state_log = run(&program, ProcessorConfig::default());
+
+    let mut csv = Vec::new();
+    write_csv(&state_log, &mut csv).unwrap();
+
+    let csv = String::from_utf8(csv).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("Cycle,PC,FreeListLength,ActiveListLength,IntegerQueueLength,Exception")
+    );
+    let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+    assert_eq!(rows.len(), state_log.len());
+    for (cycle, (row, state)) in rows.iter().zip(&state_log).enumerate() {
+        let expected = [
+            cycle.to_string(),
+            state.pc().to_string(),
+            state.free_list().len().to_string(),
+            state.active_list().len().to_string(),
+            state.integer_queue().len().to_string(),
+            state.is_exception_mode().to_string(),
+        ];
+        assert_eq!(*row, expected, "cycle {}", cycle);
+    }
+    // The division by zero puts the processor in exception mode in some cycles
+    assert!(rows.iter().any(|row| row[5] == "true"));
+    assert_eq!(rows[0], ["0", "0", "32", "0", "0", "false"]);
+}
@@ -0,0 +1,28 @@
+use cpusim::architecture::ProcessorConfig;
+use cpusim::run_program;
+
+const DIVISION: [&str; 3] = ["addi x1, x0, 12", "addi x2, x0, 4", "divu x3, x1, x2"];
+
+fn run(division_latency: usize, deadlock_cycles: usize) -> Result<u64, String> {
+    let instructions = DIVISION.iter().map(|x| x.to_string()).collect();
+    let config = ProcessorConfig {
+        division_latency,
+        deadlock_cycles,
+        ..ProcessorConfig::default()
+    };
+    let state_log = run_program(instructions, config)?;
+    Ok(state_log.last().unwrap().committed_instructions())
+}
+
+#[test]
+fn stalled_pipeline_is_reported_as_deadlock() {
+    // Nothing moves through the pipeline while the division is stuck in its ALU
+    let error = run(30, 5).unwrap_err();
+    assert!(error.starts_with("Deadlock: no progress for 5 cycles"), "{}", error);
+    assert!(error.contains("oldest instruction in flight: 2"), "{}", error);
+}
+
+#[test]
+fn slow_instructions_below_the_threshold_are_not_a_deadlock() {
+    assert_eq!(run(8, 16), Ok(3));
+}
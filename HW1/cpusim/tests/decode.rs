@@ -0,0 +1,120 @@
+use cpusim::arch_modules::Instruction;
+
+fn decode(instruction: &str) -> cpusim::arch_modules::DecodedInstruction {
+    Instruction::new(instruction.to_string()).decode(0).unwrap()
+}
+
+#[test]
+fn mv_decodes_as_addi_of_zero() {
+    assert_eq!(decode("mv x1, x2"), decode("addi x1, x2, 0"));
+}
+
+#[test]
+fn mv_requires_two_operands() {
+    assert!(Instruction::new("mv x1".to_string()).decode(0).is_err());
+    assert!(Instruction::new("mv x1, x2, x3".to_string()).decode(0).is_err());
+}
+
+#[test]
+fn assembly_source_decodes_like_the_json_program() {
+    let assembly = "addi x1, x0, 5\n\n# comment\nmulu x2, x1, x1\nsub x3, x2, x1\n";
+    let json = r#"["addi x1, x0, 5", "mulu x2, x1, x1", "sub x3, x2, x1"]"#;
+
+    let from_assembly = cpusim::parse_assembly(assembly).unwrap();
+    let instruction_strings: Vec<String> = serde_json::from_str(json).unwrap();
+    let from_json = cpusim::parse_program(&instruction_strings).unwrap();
+
+    assert_eq!(from_assembly.len(), 3);
+    assert_eq!(from_assembly.len(), from_json.len());
+    for (pc, (a, j)) in from_assembly.iter().zip(&from_json).enumerate() {
+        assert_eq!(a.decode(pc as u64), j.decode(pc as u64));
+    }
+}
+
+#[test]
+fn immediate_instructions_keep_their_mnemonic_for_display() {
+    let decoded = decode("addi x1, x2, 3");
+    assert_eq!(decoded.mnemonic, "addi");
+    assert_eq!(decoded.op_code, "add");
+
+    let decoded = decode("ADD x1, x2, x3");
+    assert_eq!(decoded.mnemonic, "add");
+    assert_eq!(decoded.op_code, "add");
+}
+
+#[test]
+fn decoded_instructions_format_back_into_assembly() {
+    for instruction in [
+        "add x1, x2, x3",
+        "addi x1, x2, -5",
+        "srai x4, x5, 3",
+        "ld x1, 8(x2)",
+        "st x3, -16(x4)",
+        "bne x1, x2, 7",
+        "jal x1, 2",
+        "lui x6, 1048576",
+        "nop",
+    ] {
+        let decoded = decode(instruction);
+        assert_eq!(decoded.to_string(), instruction);
+        assert_eq!(decode(&decoded.to_string()), decoded);
+    }
+    // Register names and spacing are normalized
+    assert_eq!(decode("MULU sp, ra, a0").to_string(), "mulu x2, x1, x10");
+    assert_eq!(decode("mv x1, x2").to_string(), "addi x1, x2, 0");
+}
+
+#[test]
+fn operands_may_be_separated_by_tabs_spaces_and_commas() {
+    let expected = decode("add x1, x2, x3");
+    assert_eq!(decode("add\tx1,\tx2,\tx3"), expected);
+    assert_eq!(decode("add\tx1\tx2\tx3"), expected);
+    assert_eq!(decode("  add   x1 ,x2 ,  x3  "), expected);
+    assert_eq!(decode("add x1,x2,x3"), expected);
+    assert_eq!(decode("mulu sp,ra,a0"), decode("mulu x2, x1, x10"));
+    assert_eq!(decode("ld\tx1,\t8(x2)"), decode("ld x1, 8(x2)"));
+}
+
+#[test]
+fn op_code_run_together_with_its_operands_is_rejected() {
+    for instruction in ["addx1,x2,x3", "add,x1,x2,x3", "addi, x1, x2, 3"] {
+        assert_eq!(
+            Instruction::new(instruction.to_string()).decode(0),
+            Err("Missing separator between op code and operands")
+        );
+    }
+}
+
+#[test]
+fn comma_without_an_operand_is_rejected() {
+    for instruction in ["add x1,, x2", "add x1, x2, x3,", "add , x1, x2, x3"] {
+        assert_eq!(
+            Instruction::new(instruction.to_string()).decode(0),
+            Err("Missing operand between commas")
+        );
+    }
+}
+
+#[test]
+fn immediates_may_be_written_in_hexadecimal() {
+    assert_eq!(decode("addi x1, x2, 0x10").immediate_value, 16);
+    assert_eq!(decode("addi x1, x2, 0XFF").immediate_value, 255);
+    assert_eq!(decode("andi x1, x2, 0xff").immediate_value, 255);
+    assert_eq!(decode("addi x1, x2, -5").immediate_value, -5);
+    assert_eq!(decode("addi x1, x2, -0x10").immediate_value, -16);
+    assert_eq!(decode("xori x1, x2, 0xFFFFFFFFFFFFFFFF").immediate_value, -1);
+    assert_eq!(decode("ld x1, 0x10(x2)"), decode("ld x1, 16(x2)"));
+    assert_eq!(decode("lui x1, 0x100"), decode("lui x1, 256"));
+}
+
+#[test]
+fn malformed_hexadecimal_immediates_are_rejected() {
+    for immediate in ["0xZZ", "0x", "0x+1", "0x10000000000000000", "1x10"] {
+        assert_eq!(
+            Instruction::new(format!("addi x1, x2, {}", immediate)).decode(0),
+            Err("Invalid immediate value"),
+            "{}",
+            immediate
+        );
+    }
+}
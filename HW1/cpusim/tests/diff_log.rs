@@ -0,0 +1,60 @@
+mod common;
+
+use cpusim::architecture::{Processor, ProcessorConfig};
+use cpusim::export::{state_delta, write_diff};
+use serde_json::Value;
+
+fn run() -> Vec<Processor> {
+    let program = ["addi x1, x0, 1", "mulu x2, x1, x1", "addi x3, x2, 2"];
+    common::run(&program, ProcessorConfig::default())
+}
+
+#[test]
+fn identical_cycles_have_an_empty_delta() {
+    let state_log = run();
+    for state in &state_log {
+        assert!(state_delta(state, &state.clone()).is_empty());
+    }
+}
+
+#[test]
+fn commit_cycle_delta_holds_only_the_changed_fields() {
+    let state_log = run();
+    let commit_cycle = state_log
+        .iter()
+        .position(|state| !state.retired_instructions().is_empty())
+        .unwrap();
+
+    let delta = state_delta(&state_log[commit_cycle - 1], &state_log[commit_cycle]);
+
+    assert!(delta.contains_key("ActiveList"));
+    // The committed instruction freed the register it replaced
+    assert!(delta.contains_key("FreeList"));
+    let previous = serde_json::to_value(&state_log[commit_cycle - 1]).unwrap();
+    let current = serde_json::to_value(&state_log[commit_cycle]).unwrap();
+    for (field, value) in current.as_object().unwrap() {
+        assert_eq!(delta.get(field).is_some(), previous[field] != *value, "{}", field);
+        if let Some(changed) = delta.get(field) {
+            assert_eq!(changed, value);
+        }
+    }
+}
+
+#[test]
+fn diff_log_keeps_the_initial_state_and_the_changing_cycles() {
+    let state_log = run();
+
+    let mut diff = Vec::new();
+    write_diff(&state_log, &mut diff).unwrap();
+
+    let diff: Value = serde_json::from_slice(&diff).unwrap();
+    let cycles = diff.as_object().unwrap();
+    assert_eq!(cycles["0"], serde_json::to_value(&state_log[0]).unwrap());
+    for cycle in 1..state_log.len() {
+        let delta = state_delta(&state_log[cycle - 1], &state_log[cycle]);
+        match cycles.get(&cycle.to_string()) {
+            Some(logged) => assert_eq!(*logged, Value::Object(delta)),
+            None => assert!(delta.is_empty(), "cycle {}", cycle),
+        }
+    }
+}
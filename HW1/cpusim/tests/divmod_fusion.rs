@@ -0,0 +1,41 @@
+mod common;
+
+use cpusim::architecture::{Processor, ProcessorConfig};
+
+const DIVMOD: [&str; 4] = ["addi x1, x0, 17", "addi x2, x0, 5", "divu x3, x1, x2", "remu x4, x1, x2"];
+const DIVISION_LATENCY: usize = 4;
+
+fn run(fuse_divmod: bool) -> Vec<Processor> {
+    let config = ProcessorConfig {
+        division_latency: DIVISION_LATENCY,
+        fuse_divmod,
+        ..ProcessorConfig::default()
+    };
+    common::run(&DIVMOD, config)
+}
+
+/// Sums the cycles every ALU spent busy over the run.
+fn busy_alu_cycles(state_log: &[Processor]) -> usize {
+    state_log
+        .iter()
+        .map(|state| state.alus().iter().filter(|alu| alu.is_busy()).count())
+        .sum()
+}
+
+#[test]
+fn fused_divmod_produces_quotient_and_remainder() {
+    for fuse_divmod in [false, true] {
+        let state_log = run(fuse_divmod);
+        let final_state = state_log.last().unwrap();
+        assert!(final_state.is_done());
+        assert_eq!(final_state.committed_instructions(), 4);
+        assert_eq!(final_state.architectural_registers()[3..5], [3, 2]);
+    }
+}
+
+#[test]
+fn fused_divmod_occupies_a_single_division() {
+    // The two additions take one busy cycle each
+    assert_eq!(busy_alu_cycles(&run(false)), 2 + 2 * DIVISION_LATENCY);
+    assert_eq!(busy_alu_cycles(&run(true)), 2 + DIVISION_LATENCY);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::run;
+use cpusim::arch_modules::ExceptionCause;
+use cpusim::architecture::ProcessorConfig;
+
+/// Runs the program and returns the cause of the exception it raised, checking that the cause
+/// label appears in the serialized log.
+fn exception_cause(program: &[&str], config: ProcessorConfig) -> Option<ExceptionCause> {
+    let state_log = run(program, config);
+
+    let cause = state_log.last().unwrap().exception_cause();
+    if let Some(cause) = cause {
+        let json = serde_json::to_string(&state_log).unwrap();
+        assert!(json.contains(&format!("\"ExceptionCause\":\"{:?}\"", cause)));
+    }
+    cause
+}
+
+#[test]
+fn division_by_zero_is_reported() {
+    let program = ["addi x1, x0, 5", "divu x2, x1, x0"];
+    assert_eq!(
+        exception_cause(&program, ProcessorConfig::default()),
+        Some(ExceptionCause::DivideByZero)
+    );
+}
+
+#[test]
+fn signed_division_overflow_is_reported() {
+    let program = ["addi x1, x0, 1", "slli x1, x1, 63", "addi x2, x0, -1", "div x3, x1, x2"];
+    assert_eq!(
+        exception_cause(&program, ProcessorConfig::default()),
+        Some(ExceptionCause::Overflow)
+    );
+}
+
+#[test]
+fn trapped_unsigned_overflow_is_reported() {
+    let program = ["addi x1, x0, 1", "slli x1, x1, 63", "add x2, x1, x1"];
+    let config = ProcessorConfig {
+        trap_on_overflow: true,
+        ..ProcessorConfig::default()
+    };
+    assert_eq!(exception_cause(&program, config), Some(ExceptionCause::Overflow));
+}
+
+#[test]
+fn invalid_address_is_reported() {
+    let program = ["addi x1, x0, 3", "ld x2, 0(x1)"];
+    assert_eq!(
+        exception_cause(&program, ProcessorConfig::default()),
+        Some(ExceptionCause::InvalidAddress)
+    );
+}
+
+#[test]
+fn no_cause_without_exception() {
+    let program = ["addi x1, x0, 3", "addi x2, x1, 4"];
+    assert_eq!(exception_cause(&program, ProcessorConfig::default()), None);
+}
@@ -0,0 +1,31 @@
+use cpusim::arch_modules::ExceptionCause;
+use cpusim::architecture::ProcessorBuilder;
+use cpusim::parse_program;
+
+#[test]
+fn consumer_of_an_underflowing_sub_never_receives_its_value() {
+    let program = ["addi x1, x0, 1", "sub x2, x0, x1", "addi x3, x2, 5", "add x4, x1, x1"];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+    let mut processor = ProcessorBuilder::new().trap_on_overflow(true).build().unwrap();
+
+    let mut forwarded_pcs = Vec::new();
+    let mut issued_pcs = Vec::new();
+    let mut exception_cause = None;
+    while processor.step(&instructions).unwrap() {
+        forwarded_pcs.extend(processor.forwarding_events().iter().map(|e| e.producer_pc));
+        issued_pcs.extend(processor.issued_instructions().iter().map(|(pc, _)| *pc));
+        exception_cause = exception_cause.or(processor.exception_cause());
+    }
+
+    assert_eq!(exception_cause, Some(ExceptionCause::Underflow));
+    // The sub's result only reaches the active list, its consumer waits until it is rolled back
+    assert!(forwarded_pcs.contains(&0));
+    assert!(!forwarded_pcs.contains(&1));
+    assert!(issued_pcs.contains(&1));
+    assert!(!issued_pcs.contains(&2));
+    // The rollback leaves the registers written after the sub untouched
+    for register in [3, 4] {
+        let physical = processor.register_map_table()[register] as usize;
+        assert_eq!(processor.physical_register_file()[physical], 0);
+    }
+}
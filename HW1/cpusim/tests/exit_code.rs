@@ -0,0 +1,36 @@
+mod common;
+
+use std::fs;
+
+use common::{run_simulator, TestDirectory};
+
+/// Simulates the program with the cycle cap, returning the exit code and the directory holding the
+/// input and the `output.json` log.
+fn exit_code(name: &str, program: &[&str], max_cycles: &str) -> (Option<i32>, TestDirectory) {
+    let directory = TestDirectory::new(&format!("exit-code-{}", name));
+    let input = directory.write_program("program.json", program);
+    let output = run_simulator(&input, &directory.join("output.json"), &[max_cycles]);
+    (output.status.code(), directory)
+}
+
+#[test]
+fn run_stopped_by_the_cycle_cap_exits_with_the_truncation_code() {
+    let program = ["addi x1, x0, 1", "mulu x2, x1, x1", "addi x3, x2, 1", "add x4, x3, x1"];
+
+    let (code, directory) = exit_code("truncated", &program, "3");
+
+    assert_eq!(code, Some(2));
+    // The truncated log is still written
+    let log = fs::read_to_string(directory.join("output.json")).unwrap();
+    assert!(log.starts_with('['));
+}
+
+#[test]
+fn completed_run_exits_successfully() {
+    assert_eq!(exit_code("completed", &["addi x1, x0, 1"], "100").0, Some(0));
+}
+
+#[test]
+fn failed_run_exits_with_the_error_code() {
+    assert_eq!(exit_code("invalid", &["foo x1, x2, x3"], "100").0, Some(1));
+}
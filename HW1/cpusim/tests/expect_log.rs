@@ -0,0 +1,46 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+use cpusim::export::{first_mismatch, LogMismatch};
+use serde_json::Value;
+
+#[test]
+fn mutated_reference_pinpoints_the_changed_cycle_and_field() {
+    let program = ["addi x1, x0, 1", "addi x2, x1, 2", "mulu x3, x2, x2"];
+    let state_log = run(&program, ProcessorConfig::default());
+    let reference: Vec<Value> = state_log
+        .iter()
+        .map(|state| serde_json::to_value(state).unwrap())
+        .collect();
+    assert_eq!(first_mismatch(&state_log, &reference), None);
+
+    let mut mutated = reference.clone();
+    mutated[3]["ActiveList"][1]["Done"] = Value::Bool(true);
+    mutated[5]["PC"] = Value::from(99);
+    assert_eq!(
+        first_mismatch(&state_log, &mutated),
+        Some(LogMismatch::Field {
+            cycle: 3,
+            field: "ActiveList[1].Done".to_string(),
+            expected: Value::Bool(true),
+            logged: Value::Bool(false),
+        })
+    );
+
+    // Fields missing from the reference are not compared
+    let mut partial = reference.clone();
+    for cycle in partial.iter_mut() {
+        cycle.as_object_mut().unwrap().remove("ExceptionCause");
+    }
+    assert_eq!(first_mismatch(&state_log, &partial), None);
+
+    let truncated = &reference[..reference.len() - 1];
+    assert_eq!(
+        first_mismatch(&state_log, truncated),
+        Some(LogMismatch::CycleCount {
+            expected: reference.len() - 1,
+            logged: reference.len(),
+        })
+    );
+}
@@ -0,0 +1,45 @@
+use cpusim::architecture::{Processor, ProcessorBuilder};
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+const PROGRAM: [&str; 4] = [
+    "addi x1, x0, 1",
+    "addi x2, x0, 2",
+    "addi x3, x0, 3",
+    "addi x4, x0, 4",
+];
+
+fn run_with_fetch_width(fetch_width: usize) -> Vec<Processor> {
+    let processor = ProcessorBuilder::new().fetch_width(fetch_width).build().unwrap();
+    let instructions = parse_program(&PROGRAM.map(String::from)).unwrap();
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+    assert!(state_log.last().unwrap().has_completed(&instructions));
+    state_log
+}
+
+fn decoded_pcs(state: &Processor) -> Vec<u64> {
+    state.decoded_instructions().iter().map(|i| i.pc).collect()
+}
+
+#[test]
+fn full_fetch_width_fills_the_decoded_buffer_in_one_cycle() {
+    let state_log = run_with_fetch_width(4);
+
+    assert_eq!(decoded_pcs(&state_log[1]), [0, 1, 2, 3]);
+    assert!(decoded_pcs(&state_log[2]).is_empty());
+}
+
+#[test]
+fn narrow_fetch_width_fills_the_decoded_buffer_over_two_cycles() {
+    let state_log = run_with_fetch_width(2);
+
+    // The first pair is dispatched while the second one is fetched
+    assert_eq!(decoded_pcs(&state_log[1]), [0, 1]);
+    assert_eq!(decoded_pcs(&state_log[2]), [2, 3]);
+    assert!(decoded_pcs(&state_log[3]).is_empty());
+    assert_eq!(state_log[3].active_list().len(), 4);
+}
+
+#[test]
+fn fetch_width_must_be_at_least_one() {
+    assert!(ProcessorBuilder::new().fetch_width(0).build().is_err());
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use std::fs;
+
+use common::{run, run_simulator, TestDirectory};
+use cpusim::architecture::ProcessorConfig;
+use cpusim::export::LogWithFinalRegisters;
+use serde_json::Value;
+
+const PROGRAM: [&str; 3] = ["addi x1, x0, 6", "addi x2, x0, 7", "mulu x3, x1, x2"];
+
+#[test]
+fn architectural_registers_resolve_the_map_table() {
+    let state_log = run(&PROGRAM, ProcessorConfig::default());
+
+    let final_state = state_log.last().unwrap();
+    let registers = final_state.architectural_registers();
+    assert_eq!(registers.len(), 32);
+    assert_eq!(registers[..4], [0, 6, 7, 42]);
+    // x3 is renamed, so its value is not in physical register 3
+    assert_ne!(final_state.register_map_table()[3], 3);
+}
+
+#[test]
+fn final_registers_are_appended_to_the_log() {
+    let state_log = run(&PROGRAM, ProcessorConfig::default());
+
+    let log = serde_json::to_value(LogWithFinalRegisters::new(&state_log)).unwrap();
+    let log = log.as_array().unwrap();
+    assert_eq!(log.len(), state_log.len() + 1);
+    assert_eq!(log[0], serde_json::to_value(&state_log[0]).unwrap());
+    let final_registers = &log.last().unwrap()["FinalRegisters"];
+    assert_eq!(final_registers["x3"], 42);
+    assert_eq!(final_registers["x31"], 0);
+    assert_eq!(final_registers.as_object().unwrap().len(), 32);
+}
+
+#[test]
+fn final_registers_flag_dumps_the_registers() {
+    let directory = TestDirectory::new("final");
+    let input = directory.write_program("product.json", &PROGRAM);
+    let output = directory.join("product_out.json");
+
+    let status = run_simulator(&input, &output, &["--final-registers"]).status;
+
+    assert!(status.success());
+    let log: Vec<Value> = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(log.last().unwrap()["FinalRegisters"]["x3"], 42);
+    assert!(log[log.len() - 2].get("PC").is_some());
+}
@@ -0,0 +1,57 @@
+use cpusim::architecture::ProcessorBuilder;
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+/// Runs the program and returns the cycle every PC was issued in.
+fn issue_cycles(program: &[&str], forwarding_bubble: bool) -> Vec<usize> {
+    let instructions =
+        parse_program(&program.iter().map(|x| x.to_string()).collect::<Vec<_>>()).unwrap();
+    let processor = ProcessorBuilder::new()
+        .forwarding_bubble(forwarding_bubble)
+        .build()
+        .unwrap();
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+    assert!(state_log.last().unwrap().has_completed(&instructions));
+    (0..program.len() as u64)
+        .map(|pc| {
+            state_log
+                .iter()
+                .position(|state| state.issued_instructions().iter().any(|(x, _)| *x == pc))
+                .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn bubble_delays_the_issue_of_a_dependent_instruction_by_one_cycle() {
+    let program = ["addi x1, x0, 1", "add x2, x1, x1", "addi x3, x2, 1", "addi x4, x0, 4"];
+
+    let without_bubble = issue_cycles(&program, false);
+    let with_bubble = issue_cycles(&program, true);
+
+    // Independent instructions are unaffected
+    assert_eq!(with_bubble[0], without_bubble[0]);
+    assert_eq!(with_bubble[3], without_bubble[3]);
+    assert_eq!(without_bubble[1] - without_bubble[0], 2);
+    assert_eq!(with_bubble[1] - with_bubble[0], 3);
+    // Every forwarded dependency pays the bubble
+    assert_eq!(with_bubble[2] - with_bubble[1], 3);
+}
+
+#[test]
+fn bubble_keeps_the_results_of_the_program() {
+    let program = ["addi x1, x0, 5", "mulu x2, x1, x1", "sub x3, x2, x1", "add x4, x3, x3"];
+    let instructions =
+        parse_program(&program.iter().map(|x| x.to_string()).collect::<Vec<_>>()).unwrap();
+    let run = |forwarding_bubble: bool| {
+        let processor = ProcessorBuilder::new()
+            .forwarding_bubble(forwarding_bubble)
+            .build()
+            .unwrap();
+        let final_state = run_on(processor, &instructions, MAX_CYCLES).unwrap().pop().unwrap();
+        let x4 = final_state.register_map_table()[4] as usize;
+        final_state.physical_register_file()[x4]
+    };
+
+    assert_eq!(run(false), 40);
+    assert_eq!(run(true), 40);
+}
@@ -0,0 +1,24 @@
+use cpusim::architecture::Processor;
+use cpusim::parse_program;
+use cpusim::trace::ForwardEvent;
+
+#[test]
+fn one_result_wakes_two_dependent_instructions() {
+    let program = ["addi x1, x0, 7", "add x2, x1, x1", "addi x3, x1, 1"];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+    let mut processor = Processor::new();
+
+    let mut events: Vec<ForwardEvent> = Vec::new();
+    while processor.step(&instructions).unwrap() {
+        events.extend_from_slice(processor.forwarding_events());
+    }
+
+    let from_first: Vec<&ForwardEvent> = events.iter().filter(|e| e.producer_pc == 0).collect();
+    assert_eq!(from_first.len(), 2);
+    assert_eq!(from_first[0].consumer_pc, 1);
+    assert_eq!(from_first[1].consumer_pc, 2);
+    for event in from_first {
+        assert_eq!(event.alu_index, 0);
+        assert_eq!(event.value, 7);
+    }
+}
@@ -0,0 +1,30 @@
+use cpusim::architecture::ProcessorBuilder;
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+#[test]
+fn tiny_register_file_stalls_dispatch_instead_of_crashing() {
+    // A single free register: each instruction waits for the previous one to retire and free
+    // the register it replaced
+    let processor = ProcessorBuilder::new()
+        .physical_registers(33)
+        .decoded_buffer_size(1)
+        .build()
+        .unwrap();
+    let program = [
+        "addi x1, x0, 1",
+        "mulu x2, x1, x1",
+        "addi x3, x2, 2",
+        "add x4, x3, x1",
+        "nop",
+    ];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.has_completed(&instructions));
+    assert_eq!(final_state.committed_instructions(), program.len() as u64);
+    assert!(final_state.stall_report().free_list > 0);
+    let x4 = final_state.register_map_table()[4] as usize;
+    assert_eq!(final_state.physical_register_file()[x4], 4);
+}
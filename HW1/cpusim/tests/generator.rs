@@ -0,0 +1,23 @@
+use cpusim::architecture::ProcessorConfig;
+use cpusim::generator::generate_program;
+use cpusim::{parse_program, run};
+
+#[test]
+fn same_seed_generates_the_same_program() {
+    assert_eq!(generate_program(40, 7), generate_program(40, 7));
+    assert_ne!(generate_program(40, 7), generate_program(40, 8));
+    assert_eq!(generate_program(40, 7).len(), 40);
+}
+
+#[test]
+fn generated_program_runs_to_completion_without_exceptions() {
+    let program = generate_program(100, 2024);
+    let instructions = parse_program(&program).unwrap();
+    let state_log = run(&instructions, ProcessorConfig::default(), 500).unwrap();
+
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.is_done());
+    assert!(final_state.has_completed(&instructions));
+    assert_eq!(final_state.committed_instructions(), 100);
+    assert!(state_log.iter().all(|state| !state.is_exception_mode()));
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+
+#[test]
+fn halt_stops_fetching_and_drains_older_instructions() {
+    let program = [
+        "addi x1, x0, 1",
+        "mulu x2, x1, x1",
+        "halt",
+        "addi x3, x0, 3",
+        "addi x4, x0, 4",
+    ];
+    let state_log = run(&program, ProcessorConfig::default());
+
+    // Nothing past the halt is ever fetched
+    assert!(state_log
+        .iter()
+        .all(|state| state.decoded_instructions().iter().all(|i| i.pc <= 2)));
+
+    // The instructions before the halt retired, so the final state is precise
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.is_halted());
+    assert!(final_state.is_done());
+    assert_eq!(final_state.committed_instructions(), 3);
+    let x2 = final_state.register_map_table()[2] as usize;
+    assert_eq!(final_state.physical_register_file()[x2], 1);
+    assert_eq!(final_state.register_map_table()[3], 3);
+    assert_eq!(final_state.register_map_table()[4], 4);
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+
+#[test]
+fn dependent_chain_never_issues_a_busy_operand() {
+    // Every instruction reads the result of the previous one, through ALUs of different latencies
+    // and a load; the debug self-check in issue panics if an operand is read before it is ready.
+    let program = [
+        "addi x1, x0, 3",
+        "mulu x2, x1, x1",
+        "add x3, x2, x1",
+        "divu x4, x3, x1",
+        "st x4, 0(x0)",
+        "ld x5, 0(x0)",
+        "sub x6, x5, x4",
+        "add x7, x6, x3",
+    ];
+    let state_log = run(&program, ProcessorConfig::default());
+
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.is_done());
+    assert_eq!(final_state.committed_instructions(), 8);
+    let x7 = final_state.register_map_table()[7] as usize;
+    assert_eq!(final_state.physical_register_file()[x7], 12);
+}
@@ -0,0 +1,25 @@
+use cpusim::architecture::ProcessorConfig;
+use cpusim::{parse_program, run, MAX_CYCLES};
+
+#[test]
+fn program_starts_at_the_initial_pc() {
+    let program = ["addi x1, x0, 5", "beq x1, x1, 1027", "addi x2, x0, 99", "addi x3, x0, 7"];
+    let instructions: Vec<String> = program.iter().map(|x| x.to_string()).collect();
+    let config = ProcessorConfig {
+        initial_pc: 0x400,
+        ..ProcessorConfig::default()
+    };
+    let state_log = run(&parse_program(&instructions).unwrap(), config, MAX_CYCLES).unwrap();
+
+    assert_eq!(state_log[0].pc(), 0x400);
+    let decoded_pcs: Vec<u64> = state_log[1].decoded_instructions().iter().map(|i| i.pc).collect();
+    assert_eq!(decoded_pcs, vec![0x400, 0x401, 0x402, 0x403]);
+
+    // The branch target is an absolute PC, skipping the instruction at 0x402
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.is_done());
+    assert_eq!(final_state.committed_instructions(), 3);
+    let x3 = final_state.register_map_table()[3] as usize;
+    assert_eq!(final_state.physical_register_file()[x3], 7);
+    assert_eq!(final_state.register_map_table()[2], 2);
+}
@@ -0,0 +1,16 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+
+#[test]
+fn instruction_mix_counts_committed_op_codes() {
+    let program = ["add x1, x0, x0", "mulu x2, x1, x1", "add x3, x2, x1"];
+    let state_log = run(&program, ProcessorConfig::default());
+
+    let mix = state_log.last().unwrap().instruction_mix();
+    assert_eq!(mix.count("add"), 2);
+    assert_eq!(mix.count("mulu"), 1);
+    assert_eq!(mix.count("sub"), 0);
+    assert_eq!(mix.to_string(), "Instruction mix: add 2, mulu 1");
+}
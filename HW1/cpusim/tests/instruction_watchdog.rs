@@ -0,0 +1,46 @@
+use cpusim::architecture::ProcessorConfig;
+use cpusim::run_program;
+
+fn run(program: &[&str], instruction_cycle_budget: usize) -> Result<u64, String> {
+    let instructions = program.iter().map(|x| x.to_string()).collect();
+    // The division outlasts the budget, the deadlock detection is kept out of the way
+    let config = ProcessorConfig {
+        division_latency: 40,
+        deadlock_cycles: 100,
+        instruction_cycle_budget,
+        ..ProcessorConfig::default()
+    };
+    let state_log = run_program(instructions, config)?;
+    Ok(state_log.last().unwrap().committed_instructions())
+}
+
+#[test]
+fn instruction_waiting_on_an_operand_past_its_budget_is_reported() {
+    let program = ["addi x1, x0, 12", "addi x2, x0, 4", "divu x3, x1, x2", "add x4, x3, x3"];
+
+    let error = run(&program, 10).unwrap_err();
+
+    assert!(error.starts_with("Instruction not retired after 10 cycles"), "{}", error);
+    assert!(error.contains("PC 2 (executing in ALU 0)"), "{}", error);
+    assert!(
+        error.contains("PC 3 (operand A never became ready, waiting on p34)"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn instructions_retiring_within_their_budget_run_to_completion() {
+    let program = ["addi x1, x0, 12", "addi x2, x0, 4", "divu x3, x1, x2", "add x4, x3, x3"];
+
+    assert_eq!(run(&program, 50), Ok(4));
+}
+
+#[test]
+fn instruction_cycle_budget_must_be_positive() {
+    let config = ProcessorConfig {
+        instruction_cycle_budget: 0,
+        ..ProcessorConfig::default()
+    };
+    assert!(config.validate().is_err());
+}
@@ -0,0 +1,71 @@
+use cpusim::architecture::{Processor, ProcessorBuilder};
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+// PCs 0 and 2 issue as soon as they are dispatched, the others wait for x1
+const PROGRAM: [&str; 8] = [
+    "mulu x1, x10, x11",
+    "addi x2, x1, 1",
+    "addi x3, x0, 3",
+    "addi x4, x1, 2",
+    "addi x5, x1, 3",
+    "addi x6, x1, 4",
+    "addi x7, x1, 5",
+    "addi x8, x1, 6",
+];
+
+fn run_with_compaction(compacting_integer_queue: bool) -> Vec<Processor> {
+    let processor = ProcessorBuilder::new()
+        .integer_queue_size(8)
+        .compacting_integer_queue(compacting_integer_queue)
+        .build()
+        .unwrap();
+    let instructions = parse_program(&PROGRAM.map(String::from)).unwrap();
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+    assert!(state_log.last().unwrap().has_completed(&instructions));
+    state_log
+}
+
+#[test]
+fn dispatch_allocates_consecutive_slots_to_an_empty_queue() {
+    for compacting_integer_queue in [false, true] {
+        let state_log = run_with_compaction(compacting_integer_queue);
+
+        let slots = state_log[2].integer_queue_slots();
+        assert_eq!(slots[..5], [Some(0), Some(1), Some(2), Some(3), None]);
+    }
+}
+
+#[test]
+fn compacting_queue_shifts_entries_down_when_others_issue() {
+    let state_log = run_with_compaction(true);
+
+    // PCs 0 and 2 left, the next instructions are allocated after the youngest remaining one
+    let slots = state_log[3].integer_queue_slots();
+    assert_eq!(
+        slots,
+        [Some(1), Some(3), Some(4), Some(5), Some(6), Some(7), None, None]
+    );
+}
+
+#[test]
+fn non_compacting_queue_reuses_the_freed_slots() {
+    let state_log = run_with_compaction(false);
+
+    let slots = state_log[3].integer_queue_slots();
+    assert_eq!(
+        slots,
+        [Some(4), Some(1), Some(5), Some(3), Some(6), Some(7), None, None]
+    );
+}
+
+#[test]
+fn both_models_issue_in_the_same_order() {
+    let trace = |state_log: &[Processor]| -> Vec<Vec<u64>> {
+        state_log
+            .iter()
+            .map(|state| state.integer_queue().iter().map(|entry| entry.pc).collect())
+            .collect()
+    };
+
+    assert_eq!(trace(&run_with_compaction(false)), trace(&run_with_compaction(true)));
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use cpusim::architecture::{Processor, ProcessorConfig};
+
+fn run(issue_width: usize) -> Vec<Processor> {
+    let program = ["addi x1, x0, 1", "addi x2, x0, 2", "addi x3, x0, 3", "addi x4, x0, 4"];
+    let config = ProcessorConfig {
+        alu_count: 4,
+        issue_width,
+        ..ProcessorConfig::default()
+    };
+    common::run(&program, config)
+}
+
+fn busy_alus(state: &Processor) -> usize {
+    state.alus().iter().filter(|alu| alu.is_busy()).count()
+}
+
+#[test]
+fn narrow_issue_leaves_alus_idle() {
+    // The four independent instructions are dispatched in cycle 2 and ready from cycle 3
+    let state_log = run(2);
+    assert_eq!(state_log[2].integer_queue().len(), 4);
+    assert_eq!(state_log[3].integer_queue().len(), 2);
+    assert_eq!(busy_alus(&state_log[3]), 2);
+    assert_eq!(state_log[4].integer_queue().len(), 0);
+    assert_eq!(busy_alus(&state_log[4]), 2);
+    for states in state_log.windows(2) {
+        assert!(states[0].integer_queue().len().saturating_sub(states[1].integer_queue().len()) <= 2);
+    }
+    assert_eq!(state_log.last().unwrap().committed_instructions(), 4);
+
+    let state_log = run(4);
+    assert_eq!(state_log[3].integer_queue().len(), 0);
+    assert_eq!(busy_alus(&state_log[3]), 4);
+}
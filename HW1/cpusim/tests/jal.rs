@@ -0,0 +1,39 @@
+use cpusim::architecture::{Processor, ProcessorBuilder};
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+/// Returns the cycles the instruction at `pc` entered and left the integer queue.
+fn queue_cycles(state_log: &[Processor], pc: u64) -> (usize, usize) {
+    let is_queued = |state: &Processor| state.integer_queue().iter().any(|entry| entry.pc == pc);
+    let dispatch_cycle = state_log.iter().position(is_queued).unwrap();
+    let queued_cycles = state_log[dispatch_cycle..]
+        .iter()
+        .take_while(|state| is_queued(state))
+        .count();
+    (dispatch_cycle, dispatch_cycle + queued_cycles)
+}
+
+#[test]
+fn jal_does_not_wait_on_a_producer_of_x0() {
+    let processor = ProcessorBuilder::new().division_latency(10).build().unwrap();
+    let program = [
+        "addi x1, x0, 6",
+        "addi x2, x0, 3",
+        "divu x0, x1, x2",
+        "jal x3, 5",
+        "addi x4, x0, 1",
+        "addi x5, x0, 1",
+    ];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    let (divu_dispatch, divu_issue) = queue_cycles(&state_log, 2);
+    let (jal_dispatch, jal_issue) = queue_cycles(&state_log, 3);
+    assert_eq!(jal_dispatch, divu_dispatch);
+    assert_eq!(jal_issue, jal_dispatch + 1);
+    assert!(jal_issue <= divu_issue);
+    // The divu wrote x0, the jal skipped PC 4
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.has_completed(&instructions));
+    assert_eq!(final_state.architectural_registers()[..6], [2, 6, 3, 4, 0, 3]);
+}
@@ -0,0 +1,37 @@
+use cpusim::architecture::ProcessorConfig;
+use cpusim::export::log_to_json;
+use cpusim::{parse_instructions_from_json, run, MAX_CYCLES};
+use serde_json::Value;
+
+#[test]
+fn json_program_parses_into_instructions() {
+    let json = r##"["addi x1, x0, 5", "# comment", "mulu x2, x1, x1"]"##;
+
+    let instructions = parse_instructions_from_json(json).unwrap();
+
+    assert_eq!(instructions.len(), 2);
+    assert_eq!(instructions[1].text(), "mulu x2, x1, x1");
+}
+
+#[test]
+fn malformed_json_program_is_rejected() {
+    let error = parse_instructions_from_json(r#"["addi x1, x0, 5""#).err().unwrap();
+    assert!(error.starts_with("Invalid instruction file"));
+    assert!(parse_instructions_from_json(r#"{"PC": 0}"#).is_err());
+    assert!(parse_instructions_from_json(r#"["beq x1, x2, nowhere"]"#).is_err());
+}
+
+#[test]
+fn log_serializes_to_a_json_array_of_cycles() {
+    let instructions = parse_instructions_from_json(r#"["addi x1, x0, 5", "addi x2, x1, 1"]"#)
+        .unwrap();
+    let state_log = run(&instructions, ProcessorConfig::default(), MAX_CYCLES).unwrap();
+
+    let json = log_to_json(&state_log).unwrap();
+
+    let cycles: Vec<Value> = serde_json::from_str(&json).unwrap();
+    assert_eq!(cycles.len(), state_log.len());
+    assert_eq!(cycles[0]["PC"], 0);
+    assert_eq!(cycles.last().unwrap()["PC"], state_log.last().unwrap().pc());
+    assert_eq!(json, serde_json::to_string_pretty(&state_log).unwrap());
+}
@@ -0,0 +1,42 @@
+use cpusim::architecture::ProcessorConfig;
+use cpusim::{parse_program, run, MAX_CYCLES};
+
+fn entries(program: &[&str]) -> Vec<String> {
+    program.iter().map(|x| x.to_string()).collect()
+}
+
+#[test]
+fn branch_to_label_decodes_to_its_pc() {
+    let program = [
+        "addi x1, x0, 3",
+        "loop:",
+        "subi x1, x1, 1",
+        "addi x2, x2, 2",
+        "bne x1, x0, loop",
+        "jal x3, end",
+        "addi x4, x0, 99",
+        "end:",
+    ];
+    let instructions = parse_program(&entries(&program)).unwrap();
+
+    // Labels take no PC: "loop" is the PC of the subi, "end" the PC past the last instruction
+    assert_eq!(instructions.len(), 6);
+    assert_eq!(instructions[3].decode(3).unwrap().immediate_value, 1);
+    assert_eq!(instructions[4].decode(4).unwrap().immediate_value, 6);
+
+    let state_log = run(&instructions, ProcessorConfig::default(), MAX_CYCLES).unwrap();
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.is_done());
+    let x2 = final_state.register_map_table()[2] as usize;
+    assert_eq!(final_state.physical_register_file()[x2], 6);
+    assert_eq!(final_state.register_map_table()[4], 4);
+}
+
+#[test]
+fn undefined_and_duplicate_labels_are_rejected() {
+    let undefined = parse_program(&entries(&["beq x1, x2, nowhere"]));
+    assert_eq!(undefined.err().as_deref(), Some("Undefined label: nowhere"));
+
+    let duplicate = parse_program(&entries(&["loop:", "nop", "loop:", "beq x1, x2, loop"]));
+    assert_eq!(duplicate.err().as_deref(), Some("Duplicate label: loop"));
+}
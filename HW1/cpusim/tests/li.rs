@@ -0,0 +1,46 @@
+mod common;
+
+use common::run;
+use cpusim::arch_modules::Instruction;
+use cpusim::architecture::ProcessorConfig;
+use cpusim::run_program;
+
+/// Runs the program and returns the final value of x1.
+fn x1_after(program: &[&str], hardwired_zero_register: bool) -> u64 {
+    let config = ProcessorConfig {
+        hardwired_zero_register,
+        ..ProcessorConfig::default()
+    };
+    let state_log = run(program, config);
+    let final_state = state_log.last().unwrap();
+    let physical_register = final_state.register_map_table()[1] as usize;
+    final_state.physical_register_file()[physical_register]
+}
+
+#[test]
+fn li_decodes_as_addi_from_x0() {
+    let decode = |instruction: &str| Instruction::new(instruction.to_string()).decode(0).unwrap();
+    assert_eq!(decode("li x1, 42"), decode("addi x1, x0, 42"));
+    assert_eq!(decode("LI a0, -7"), decode("addi x10, x0, -7"));
+    assert_eq!(decode("li x1, 9223372036854775807").immediate_value, i64::MAX);
+    assert!(Instruction::new("li x1".to_string()).decode(0).is_err());
+    assert!(Instruction::new("li x1, x2, 3".to_string()).decode(0).is_err());
+}
+
+#[test]
+fn li_loads_the_immediate() {
+    assert_eq!(x1_after(&["li x1, 42"], true), 42);
+    assert_eq!(x1_after(&["li x1, -1"], true), u64::MAX);
+}
+
+#[test]
+fn li_needs_x0_to_read_zero() {
+    // Once a program wrote x0, only the hardwired zero register keeps li correct
+    let program = ["addi x0, x0, 5", "li x1, 42"];
+    assert_eq!(x1_after(&program, true), 42);
+
+    let instructions = program.map(String::from).to_vec();
+    let error = run_program(instructions, ProcessorConfig::default()).err();
+    let expected = "li needs the hardwired zero register at index 1: li x1, 42";
+    assert_eq!(error.as_deref(), Some(expected));
+}
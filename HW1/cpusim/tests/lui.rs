@@ -0,0 +1,62 @@
+mod common;
+
+use common::run;
+use cpusim::arch_modules::Instruction;
+use cpusim::architecture::{Processor, ProcessorBuilder, ProcessorConfig};
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+#[test]
+fn lui_and_addi_build_64_bit_constants() {
+    let program = [
+        "lui x1, 1048576",
+        "addi x1, x1, 5",
+        "lui x2, 4503599627370495",
+        "addi x2, x2, 4095",
+        "lui x3, -1",
+    ];
+    let state_log = run(&program, ProcessorConfig::default());
+
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.is_done());
+    let registers = final_state.architectural_registers();
+    assert_eq!(registers[1], (1 << 32) + 5);
+    assert_eq!(registers[2], u64::MAX);
+    assert_eq!(registers[3], 0xFFFF_FFFF_FFFF_F000);
+}
+
+#[test]
+fn lui_immediate_must_fit_after_the_shift() {
+    let too_large = Instruction::new("lui x1, 4503599627370496".to_string());
+    assert_eq!(too_large.decode(0).err(), Some("Invalid immediate value"));
+    let missing_operand = Instruction::new("lui x1".to_string());
+    assert_eq!(missing_operand.decode(0).err(), Some("Invalid instruction format"));
+}
+
+#[test]
+fn lui_does_not_wait_on_a_producer_of_x0() {
+    let processor = ProcessorBuilder::new().division_latency(10).build().unwrap();
+    let program = ["addi x1, x0, 6", "divu x0, x1, x1", "lui x2, 1"];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    // Both are dispatched in cycle 2, the lui issues in cycle 3 without waiting for the divu
+    let is_lui_queued = |state: &Processor| state.integer_queue().iter().any(|e| e.pc == 2);
+    assert!(is_lui_queued(&state_log[2]));
+    assert!(!is_lui_queued(&state_log[3]));
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.has_completed(&instructions));
+    assert_eq!(final_state.architectural_registers()[2], 1 << 12);
+}
+
+#[test]
+fn lui_uses_no_register_file_read_port() {
+    let processor = ProcessorBuilder::new().read_ports(2).build().unwrap();
+    let program = ["lui x1, 1", "lui x2, 2", "lui x3, 3", "lui x4, 4"];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    assert_eq!(state_log[2].active_list().len(), 4);
+    assert_eq!(state_log.last().unwrap().stall_report().read_ports, 0);
+}
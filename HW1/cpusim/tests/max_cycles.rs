@@ -0,0 +1,46 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{run_simulator, TestDirectory};
+use serde_json::Value;
+
+const PROGRAM: [&str; 4] = [
+    "addi x1, x0, 1",
+    "mulu x2, x1, x1",
+    "addi x3, x2, 1",
+    "add x4, x3, x1",
+];
+
+/// Returns the number of states in the JSON log.
+fn logged_states(output: &Path) -> usize {
+    let log: Value = serde_json::from_str(&fs::read_to_string(output).unwrap()).unwrap();
+    log.as_array().unwrap().len()
+}
+
+#[test]
+fn cycle_cap_argument_limits_the_logged_states() {
+    let directory = TestDirectory::new("max-cycles-cap");
+    let input = directory.write_program("program.json", &PROGRAM);
+    let output = directory.join("output.json");
+
+    let run = run_simulator(&input, &output, &["3"]);
+
+    assert_eq!(logged_states(&output), 3);
+    let stderr = String::from_utf8(run.stderr).unwrap();
+    assert!(stderr.contains("stopped after 3 cycles"), "{}", stderr);
+}
+
+#[test]
+fn run_without_cycle_cap_argument_completes() {
+    let directory = TestDirectory::new("max-cycles-default");
+    let input = directory.write_program("program.json", &PROGRAM);
+    let output = directory.join("output.json");
+
+    let run = run_simulator(&input, &output, &[]);
+
+    assert!(run.status.success());
+    assert!(logged_states(&output) > 3);
+    assert!(!String::from_utf8(run.stderr).unwrap().contains("Warning"));
+}
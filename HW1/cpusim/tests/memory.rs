@@ -0,0 +1,30 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+
+#[test]
+fn stores_are_read_back_by_later_loads() {
+    let state_log = run(
+        &[
+            "addi x1, x0, 42",
+            "addi x2, x0, 16",
+            "st x1, 8(x2)",
+            "addi x3, x1, 1",
+            "ld x4, 24(x0)",
+            "ld x5, -8(x2)",
+            "st x3, 0(x2)",
+            "add x6, x4, x3",
+            "ld x7, 16(x0)",
+            "addi x8, x7, 1",
+            "st x8, (x0)",
+            "ld x9, 0(x0)",
+        ],
+        ProcessorConfig::default(),
+    );
+    let final_state = state_log.last().unwrap();
+
+    // Addresses are in bytes over 64-bit words, so byte address 24 is word 3
+    assert_eq!(final_state.memory()[..5], [44, 0, 43, 42, 0]);
+    assert_eq!(final_state.architectural_registers()[4..10], [42, 0, 85, 43, 44, 44]);
+}
@@ -0,0 +1,52 @@
+use cpusim::architecture::{Processor, ProcessorBuilder};
+use cpusim::export::{memory_from_le_bytes, memory_to_le_bytes};
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+#[test]
+fn load_reads_memory_initialized_from_bytes() {
+    // Two little-endian words: 0 at address 0 and 0x0102030405060708 at address 8
+    let mut image = vec![0u8; 8];
+    image.extend([0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]);
+    let words = memory_from_le_bytes(&image).unwrap();
+    assert_eq!(words, vec![0, 0x0102030405060708]);
+
+    let mut processor = Processor::new();
+    processor.load_memory(&words).unwrap();
+    let program = ["ld x1, 8(x0)", "addi x2, x1, 1", "st x2, 16(x0)"];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    let final_state = state_log.last().unwrap();
+    let x1 = final_state.register_map_table()[1] as usize;
+    assert_eq!(final_state.physical_register_file()[x1], 0x0102030405060708);
+    let dump = memory_to_le_bytes(final_state.memory());
+    assert_eq!(dump.len(), final_state.memory().len() * 8);
+    assert_eq!(dump[..16], image[..]);
+    assert_eq!(dump[16..24], [0x09, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]);
+}
+
+#[test]
+fn invalid_memory_images_are_rejected() {
+    assert!(memory_from_le_bytes(&[0; 12]).is_err());
+    let mut processor = Processor::new();
+    let too_large = vec![0; processor.memory().len() + 1];
+    assert!(processor.load_memory(&too_large).is_err());
+}
+
+#[test]
+fn load_into_hardwired_x0_is_discarded() {
+    let mut processor = ProcessorBuilder::new()
+        .hardwired_zero_register(true)
+        .build()
+        .unwrap();
+    processor.load_memory(&[7]).unwrap();
+    // The addi reads x0 once the load retired
+    let program = ["ld x0, 0(x1)", "nop", "nop", "nop", "nop", "addi x2, x0, 1"];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.has_completed(&instructions));
+    assert_eq!(final_state.architectural_registers()[0], 0);
+    assert_eq!(final_state.architectural_registers()[2], 1);
+}
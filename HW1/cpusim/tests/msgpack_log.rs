@@ -0,0 +1,34 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+use cpusim::export::{write_json_from_msgpack, write_msgpack};
+
+#[test]
+fn msgpack_log_decodes_back_into_the_json_log() {
+    // The division by zero raises an exception, so the log holds every kind of field
+    let program = [
+        "addi x1, x0, 6",
+        "mulu x2, x1, x1",
+        "st x2, 8(x0)",
+        "ld x3, 8(x0)",
+        "divu x4, x3, x0",
+        "addi x5, x0, 1",
+    ];
+    let state_log = run(&program, ProcessorConfig::default());
+
+    let mut msgpack = Vec::new();
+    write_msgpack(&state_log, &mut msgpack).unwrap();
+    let mut json = Vec::new();
+    write_json_from_msgpack(&msgpack, &mut json).unwrap();
+
+    let expected = serde_json::to_string_pretty(&state_log).unwrap();
+    assert_eq!(String::from_utf8(json).unwrap(), expected);
+    assert!(msgpack.len() * 4 < expected.len());
+}
+
+#[test]
+fn invalid_msgpack_is_rejected() {
+    let mut json = Vec::new();
+    assert!(write_json_from_msgpack(&[0xc1], &mut json).is_err());
+}
@@ -0,0 +1,15 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+
+#[test]
+fn mv_copies_the_source_register() {
+    let program = ["addi x2, x0, 42", "mv x1, x2", "addi x3, x1, 1"];
+    let state_log = run(&program, ProcessorConfig::default());
+
+    let final_state = state_log.last().unwrap();
+    let registers = final_state.architectural_registers();
+    assert_eq!(registers[1], 42);
+    assert_eq!(registers[3], 43);
+}
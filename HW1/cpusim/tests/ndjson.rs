@@ -0,0 +1,91 @@
+mod common;
+
+use std::fs;
+
+use common::{run_simulator, TestDirectory};
+use cpusim::architecture::Processor;
+use cpusim::export::write_ndjson_state;
+use cpusim::{parse_program, run_on, stream_on, MAX_CYCLES};
+use serde_json::Value;
+
+const PROGRAM: [&str; 5] = [
+    "addi x1, x0, 3",
+    "addi x2, x0, 4",
+    "mulu x3, x1, x2",
+    "sub x4, x3, x1",
+    "add x5, x4, x2",
+];
+
+#[test]
+fn streamed_states_are_the_logged_states() {
+    let instructions = parse_program(&PROGRAM.map(String::from)).unwrap();
+    let state_log = run_on(Processor::new(), &instructions, MAX_CYCLES).unwrap();
+
+    let mut ndjson = Vec::new();
+    let final_state = stream_on(Processor::new(), &instructions, MAX_CYCLES, |state| {
+        write_ndjson_state(state, &mut ndjson).map_err(|e| e.to_string())
+    })
+    .unwrap();
+
+    assert!(final_state.has_completed(&instructions));
+    let lines: Vec<Value> = String::from_utf8(ndjson)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let logged: Vec<Value> = state_log
+        .iter()
+        .map(|state| serde_json::to_value(state).unwrap())
+        .collect();
+    assert_eq!(lines, logged);
+}
+
+#[test]
+fn streaming_stops_at_the_first_error() {
+    let instructions = parse_program(&PROGRAM.map(String::from)).unwrap();
+    let mut streamed_states = 0;
+
+    let error = stream_on(Processor::new(), &instructions, MAX_CYCLES, |_| {
+        streamed_states += 1;
+        if streamed_states == 3 {
+            return Err("Output closed".to_string());
+        }
+        Ok(())
+    })
+    .err()
+    .unwrap();
+
+    assert_eq!(error, "Output closed");
+    assert_eq!(streamed_states, 3);
+}
+
+#[test]
+fn ndjson_format_writes_one_json_object_per_cycle() {
+    let directory = TestDirectory::new("ndjson");
+    let input = directory.write_program("program.json", &PROGRAM);
+    let succeeds = |output: &str, flags: &[&str]| {
+        run_simulator(&input, &directory.join(output), flags).status.success()
+    };
+
+    assert!(succeeds("log.json", &[]));
+    assert!(succeeds("log.ndjson", &["--format", "ndjson"]));
+
+    let log: Vec<Value> =
+        serde_json::from_str(&fs::read_to_string(directory.join("log.json")).unwrap()).unwrap();
+    let ndjson = fs::read_to_string(directory.join("log.ndjson")).unwrap();
+    let lines: Vec<Value> = ndjson
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), log.len());
+    assert_eq!(lines, log);
+
+    assert!(succeeds("quiet.ndjson", &["--format", "ndjson", "--quiet"]));
+    let quiet_log = fs::read_to_string(directory.join("quiet.ndjson")).unwrap();
+    assert_eq!(quiet_log.lines().count(), 1);
+    for flag in ["--trace", "--verbose"] {
+        let ndjson_flags = ["--format", "ndjson", flag];
+        assert!(!succeeds("log.ndjson", &ndjson_flags));
+        assert!(!succeeds("quiet.ndjson", &[&ndjson_flags[..], &["--quiet"]].concat()));
+    }
+}
@@ -0,0 +1,25 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+
+#[test]
+fn nop_allocates_no_register_and_commits_in_order() {
+    let program = ["addi x1, x0, 1", "nop", "addi x2, x1, 1"];
+    let state_log = run(&program, ProcessorConfig::default());
+
+    // Cycle 2 dispatches all three instructions: only the two additions take a free register
+    let initial_free_registers = state_log[0].free_list().len();
+    let dispatched = &state_log[2];
+    assert_eq!(dispatched.free_list().len(), initial_free_registers - 2);
+    assert_eq!(dispatched.integer_queue().len(), 2);
+    let nop_entry = &dispatched.active_list()[1];
+    assert_eq!(nop_entry.pc, 1);
+    assert!(nop_entry.is_done);
+
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.is_done());
+    assert_eq!(final_state.committed_instructions(), 3);
+    let x2 = final_state.register_map_table()[2] as usize;
+    assert_eq!(final_state.physical_register_file()[x2], 2);
+}
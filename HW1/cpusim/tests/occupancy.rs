@@ -0,0 +1,72 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+use cpusim::run_program;
+use cpusim::statistics::Occupancy;
+
+#[test]
+fn occupancy_counts_the_entries_of_every_structure() {
+    let program = [
+        "addi x1, x0, 3",
+        "mulu x2, x1, x1",
+        "mulu x3, x2, x1",
+        "addi x4, x0, 1",
+        "add x5, x3, x4",
+    ];
+    let config = ProcessorConfig {
+        mulu_latency: 3,
+        ..ProcessorConfig::default()
+    };
+    let state_log = run(&program, config);
+
+    assert_eq!(state_log[0].occupancy().active_list, 0);
+    assert_eq!(state_log[0].occupancy().free_list, 32);
+    for state in &state_log {
+        let occupancy = state.occupancy();
+        assert_eq!(
+            occupancy,
+            Occupancy {
+                active_list: state.active_list().len(),
+                integer_queue: state.integer_queue().len(),
+                busy_alus: state.alus().iter().filter(|alu| alu.is_busy()).count(),
+                free_list: state.free_list().len(),
+            }
+        );
+        // Every instruction in flight writes a register, renamed from the free list
+        assert_eq!(occupancy.active_list + occupancy.free_list, 32);
+    }
+    // The four first instructions are dispatched together in cycle 2, the last one after them
+    let dispatched = &state_log[2].occupancy();
+    assert_eq!(dispatched.active_list, 4);
+    assert_eq!(dispatched.integer_queue, 4);
+    // The dependent multiplications keep an ALU busy after the others are done
+    assert!(state_log.iter().any(|state| state.occupancy().busy_alus > 0
+        && state.occupancy().integer_queue > 0));
+}
+
+#[test]
+fn peak_occupancy_reaches_the_size_of_a_saturated_integer_queue() {
+    // The nops leave the multiplication alone in its dispatch group, so it issues before the
+    // dependent addi fill the integer queue four at a time
+    let mut program = vec!["nop".to_string(); 3];
+    program.push("mulu x1, x0, x0".to_string());
+    program.extend((0..40).map(|i| format!("addi x{}, x1, {}", 2 + i % 30, i)));
+    let config = ProcessorConfig {
+        mulu_latency: 12,
+        active_list_size: 64,
+        physical_register_file_size: 128,
+        ..ProcessorConfig::default()
+    };
+    let integer_queue_size = config.integer_queue_size;
+    let state_log = run_program(program, config).unwrap();
+
+    let peak_occupancy = state_log.last().unwrap().peak_occupancy();
+    assert_eq!(peak_occupancy.max_integer_queue_len, integer_queue_size);
+    let max_active_list_len = state_log
+        .iter()
+        .map(|state| state.active_list().len())
+        .max()
+        .unwrap();
+    assert_eq!(peak_occupancy.max_active_list_len, max_active_list_len);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+use cpusim::run_program;
+
+#[test]
+fn dependent_pair_dispatched_together_waits_for_the_producer() {
+    let program = ["addi x1, x0, 5", "add x2, x1, x1"];
+    let state_log = run(&program, ProcessorConfig::default());
+
+    // Both instructions are dispatched in cycle 2: the consumer waits on the producer's register
+    let dispatched = &state_log[2];
+    let producer_register = dispatched.register_map_table()[1];
+    let consumer = &dispatched.integer_queue()[1];
+    assert!(!consumer.op_a_is_ready && !consumer.op_b_is_ready);
+    assert_eq!(consumer.op_a_reg_tag, producer_register);
+    assert_eq!(consumer.op_b_reg_tag, producer_register);
+
+    assert_eq!(state_log.last().unwrap().architectural_registers()[2], 10);
+}
+
+#[test]
+fn consumer_captures_the_producer_value_whatever_the_dispatch_cycle() {
+    // Dispatching one instruction per cycle and delaying the consumer by a growing number of nops
+    // makes it dispatch before, during and after the cycle the producer writes back.
+    for nops in 0..6 {
+        let mut program = vec!["addi x1, x0, 5".to_string()];
+        program.extend((0..nops).map(|_| "nop".to_string()));
+        program.push("add x2, x1, x1".to_string());
+        let config = ProcessorConfig {
+            decoded_buffer_size: 1,
+            ..ProcessorConfig::default()
+        };
+        let state_log = run_program(program, config).unwrap();
+        let final_state = state_log.last().unwrap();
+        assert!(final_state.is_done(), "{} nops", nops);
+        assert_eq!(final_state.architectural_registers()[2], 10, "{} nops", nops);
+    }
+}
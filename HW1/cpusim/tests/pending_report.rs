@@ -0,0 +1,64 @@
+use cpusim::architecture::{Processor, ProcessorBuilder};
+use cpusim::statistics::PendingStatus;
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+#[test]
+fn truncated_run_reports_the_instructions_that_never_committed() {
+    // The long multiplication holds back its consumer and the retirement of everything younger
+    let program = [
+        "addi x1, x0, 3",
+        "mulu x2, x1, x1",
+        "addi x3, x2, 1",
+        "addi x4, x1, 1",
+        "addi x5, x0, 5",
+        "addi x6, x0, 6",
+    ];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+    let processor = ProcessorBuilder::new()
+        .mulu_latency(8)
+        .decoded_buffer_size(4)
+        .build()
+        .unwrap();
+
+    let state_log = run_on(processor, &instructions, 8).unwrap();
+
+    let final_state = state_log.last().unwrap();
+    assert!(!final_state.has_completed(&instructions));
+    let report = final_state.pending_report();
+    assert_eq!(report.pcs(), [1, 2, 3, 4, 5]);
+    assert_eq!(
+        report.instructions,
+        [
+            (1, PendingStatus::Executing),
+            (2, PendingStatus::Queued),
+            (3, PendingStatus::Done),
+            (4, PendingStatus::Done),
+            (5, PendingStatus::Done),
+        ]
+    );
+    assert_eq!(
+        report.to_string(),
+        "Pending instructions: PC 1 (executing), PC 2 (waiting in the integer queue), \
+         PC 3 (done but not committed), PC 4 (done but not committed), \
+         PC 5 (done but not committed)"
+    );
+}
+
+#[test]
+fn decoded_instructions_are_pending() {
+    let instructions = parse_program(&["addi x1, x0, 1".to_string()]).unwrap();
+    let mut processor = Processor::new();
+    processor.step(&instructions).unwrap();
+
+    assert_eq!(processor.pending_report().instructions, [(0, PendingStatus::Decoded)]);
+}
+
+#[test]
+fn completed_run_has_no_pending_instruction() {
+    let instructions = parse_program(&["addi x1, x0, 1".to_string()]).unwrap();
+    let state_log = run_on(Processor::new(), &instructions, MAX_CYCLES).unwrap();
+
+    let report = state_log.last().unwrap().pending_report();
+    assert!(report.instructions.is_empty());
+    assert_eq!(report.to_string(), "Pending instructions: none");
+}
@@ -0,0 +1,42 @@
+use std::time::{Duration, Instant};
+
+use cpusim::architecture::Processor;
+use cpusim::generator::generate_program;
+use cpusim::{parse_program, run_on, run_on_final_only};
+
+/// Generous bound on the simulation of the program below, which takes well under a tenth of it
+/// in a debug build, so only a regression of the cost of a cycle fails the test.
+const TIME_BUDGET: Duration = Duration::from_secs(2);
+const MAX_CYCLES: usize = 5000;
+
+#[test]
+fn thousand_instruction_program_runs_within_the_time_budget() {
+    let instructions = parse_program(&generate_program(1000, 15)).unwrap();
+
+    let start = Instant::now();
+    let state_log = run_on(Processor::new(), &instructions, MAX_CYCLES).unwrap();
+    let elapsed = start.elapsed();
+
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.has_completed(&instructions));
+    assert_eq!(final_state.committed_instructions(), 1000);
+    assert!(
+        elapsed < TIME_BUDGET,
+        "{} cycles took {:?}",
+        state_log.len(),
+        elapsed
+    );
+}
+
+#[test]
+fn final_only_run_of_a_long_program_matches_the_full_log() {
+    let instructions = parse_program(&generate_program(1000, 15)).unwrap();
+
+    let full_log = run_on(Processor::new(), &instructions, MAX_CYCLES).unwrap();
+    let final_only_log = run_on_final_only(Processor::new(), &instructions, MAX_CYCLES).unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&final_only_log[0]).unwrap(),
+        serde_json::to_value(full_log.last().unwrap()).unwrap()
+    );
+}
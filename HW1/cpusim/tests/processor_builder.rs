@@ -0,0 +1,39 @@
+use cpusim::architecture::ProcessorBuilder;
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+#[test]
+fn eight_alu_machine_issues_eight_instructions_at_once() {
+    let processor = ProcessorBuilder::new()
+        .alu_count(8)
+        .issue_width(8)
+        .fetch_width(8)
+        .decoded_buffer_size(8)
+        .build()
+        .unwrap();
+    assert_eq!(processor.alus().len(), 8);
+    let program: Vec<String> = (1..=8)
+        .map(|register| format!("addi x{}, x0, {}", register, register))
+        .collect();
+    let instructions = parse_program(&program).unwrap();
+
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    // The eight instructions are dispatched in cycle 2 and all issued in cycle 3
+    assert_eq!(state_log[2].integer_queue().len(), 8);
+    assert!(state_log[3].integer_queue().is_empty());
+    assert!(state_log[3].alus().iter().all(|alu| alu.is_busy()));
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.has_completed(&instructions));
+    assert_eq!(final_state.architectural_registers()[1..=8], [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn physical_register_file_must_exceed_the_logical_registers() {
+    for physical_registers in [16, 31, 32] {
+        assert_eq!(
+            ProcessorBuilder::new().physical_registers(physical_registers).build().err(),
+            Some("Physical register file must be larger than the 32 logical registers")
+        );
+    }
+    assert!(ProcessorBuilder::new().physical_registers(36).build().is_ok());
+}
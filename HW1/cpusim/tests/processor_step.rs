@@ -0,0 +1,60 @@
+use cpusim::architecture::Processor;
+use cpusim::parse_program;
+
+#[test]
+fn step_runs_until_the_program_completes() {
+    let program = [
+        "addi x1, x0, 3",
+        "addi x2, x0, 4",
+        "add x3, x1, x2",
+        "sub x4, x3, x1",
+        "mulu x5, x4, x2",
+    ];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+    let mut processor = Processor::new();
+
+    let mut cycles = 0;
+    while processor.step(&instructions).unwrap() {
+        cycles += 1;
+        assert!(cycles < 50, "program did not complete");
+    }
+
+    assert!(processor.active_list().is_empty());
+    assert_eq!(processor.committed_instructions(), 5);
+    assert!(!processor.step(&instructions).unwrap());
+}
+
+#[test]
+fn propagate_n_stops_at_completion_with_the_cycle_count_of_single_steps() {
+    let program = [
+        "addi x1, x0, 3",
+        "mulu x2, x1, x1",
+        "divu x3, x2, x1",
+        "add x4, x3, x2",
+    ];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+    let mut stepped = Processor::new();
+    let mut stepped_cycles = 0;
+    while stepped.step(&instructions).unwrap() {
+        stepped_cycles += 1;
+    }
+
+    let mut processor = Processor::new();
+    let cycles = processor.propagate_n(&instructions, 1_000).unwrap();
+
+    // The last step completes the program, so it is a cycle as well
+    assert_eq!(cycles, stepped_cycles + 1);
+    assert_eq!(processor.cycle(), cycles as u64);
+    assert!(processor.has_completed(&instructions));
+    assert_eq!(processor.propagate_n(&instructions, 1_000).unwrap(), 0);
+}
+
+#[test]
+fn propagate_n_runs_at_most_the_given_cycles() {
+    let instructions = parse_program(&["addi x1, x0, 1".to_string()]).unwrap();
+    let mut processor = Processor::new();
+
+    assert_eq!(processor.propagate_n(&instructions, 2).unwrap(), 2);
+    assert_eq!(processor.cycle(), 2);
+    assert!(!processor.has_completed(&instructions));
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::{run_simulator, TestDirectory};
+use cpusim::architecture::ProcessorBuilder;
+use cpusim::{check_program_size, parse_program, parse_program_at, run_on, MAX_CYCLES};
+
+const PROGRAM: [&str; 3] = ["addi x1, x0, 1", "addi x2, x0, 2", "add x3, x1, x2"];
+
+#[test]
+fn program_at_the_size_limit_is_accepted() {
+    let instructions = parse_program(&PROGRAM.map(String::from)).unwrap();
+
+    assert!(check_program_size(&instructions, 3).is_ok());
+}
+
+#[test]
+fn program_over_the_size_limit_is_rejected_naming_the_limit() {
+    let instructions = parse_program(&PROGRAM.map(String::from)).unwrap();
+
+    let error = check_program_size(&instructions, 2).unwrap_err();
+    assert_eq!(error, "Program has 3 instructions, more than the maximum of 2");
+}
+
+#[test]
+fn fetching_past_the_last_pc_fails_instead_of_overflowing() {
+    let processor = ProcessorBuilder::new().initial_pc(u64::MAX).build().unwrap();
+    let instructions = parse_program_at(&["addi x1, x0, 1".to_string()], u64::MAX).unwrap();
+
+    let error = run_on(processor, &instructions, MAX_CYCLES).err().unwrap();
+    assert!(error.contains("PC overflow"), "{}", error);
+}
+
+#[test]
+fn max_program_size_flag_limits_the_input_program() {
+    let directory = TestDirectory::new("size");
+    let input = directory.write_program("program.json", &PROGRAM);
+    let output = directory.join("output.json");
+    let run_with_limit =
+        |max_program_size| run_simulator(&input, &output, &["--max-program-size", max_program_size]);
+
+    assert!(run_with_limit("3").status.success());
+
+    let rejected = run_with_limit("2");
+    assert!(!rejected.status.success());
+    let stderr = String::from_utf8(rejected.stderr).unwrap();
+    assert!(stderr.contains("more than the maximum of 2"), "{}", stderr);
+    assert!(!run_with_limit("many").status.success());
+}
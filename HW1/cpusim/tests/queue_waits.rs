@@ -0,0 +1,26 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+
+#[test]
+fn dependent_chain_waits_longer_in_the_integer_queue() {
+    // Dispatched in the same cycle, every mulu waits for the result of the previous one
+    let program = [
+        "addi x1, x0, 2",
+        "mulu x2, x1, x1",
+        "mulu x3, x2, x2",
+        "mulu x4, x3, x3",
+    ];
+    let state_log = run(&program, ProcessorConfig::default());
+
+    let waits = state_log.last().unwrap().queue_waits();
+    let wait_cycles: Vec<u64> = waits.counts.keys().copied().collect();
+    assert_eq!(wait_cycles, [1, 3, 5, 7]);
+    assert!(waits.counts.values().all(|&count| count == 1));
+    assert_eq!(waits.count(2), 0);
+    assert_eq!(
+        waits.to_string(),
+        "Integer queue waits: 1 cycles 1, 3 cycles 1, 5 cycles 1, 7 cycles 1"
+    );
+}
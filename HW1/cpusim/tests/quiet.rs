@@ -0,0 +1,69 @@
+mod common;
+
+use std::fs;
+
+use common::{run_simulator, TestDirectory};
+use cpusim::architecture::Processor;
+use cpusim::{parse_program, run_on, run_on_final_only, MAX_CYCLES};
+use serde_json::Value;
+
+const PROGRAM: [&str; 5] = [
+    "addi x1, x0, 3",
+    "addi x2, x0, 4",
+    "mulu x3, x1, x2",
+    "sub x4, x3, x1",
+    "add x5, x4, x2",
+];
+
+#[test]
+fn final_only_run_keeps_the_final_state() {
+    let instructions = parse_program(&PROGRAM.map(String::from)).unwrap();
+    let full_log = run_on(Processor::new(), &instructions, MAX_CYCLES).unwrap();
+
+    let final_only_log = run_on_final_only(Processor::new(), &instructions, MAX_CYCLES).unwrap();
+
+    assert_eq!(final_only_log.len(), 1);
+    let final_state = &final_only_log[0];
+    assert_eq!(
+        serde_json::to_value(final_state).unwrap(),
+        serde_json::to_value(full_log.last().unwrap()).unwrap()
+    );
+    assert_eq!(final_state.cycle(), full_log.len() as u64 - 1);
+}
+
+#[test]
+fn final_only_run_stops_at_the_cycle_cap() {
+    let instructions = parse_program(&PROGRAM.map(String::from)).unwrap();
+    let full_log = run_on(Processor::new(), &instructions, 4).unwrap();
+
+    let final_only_log = run_on_final_only(Processor::new(), &instructions, 4).unwrap();
+
+    assert_eq!(final_only_log.len(), 1);
+    assert!(!final_only_log[0].has_completed(&instructions));
+    assert_eq!(final_only_log[0].cycle(), 3);
+    assert_eq!(
+        serde_json::to_value(&final_only_log[0]).unwrap(),
+        serde_json::to_value(full_log.last().unwrap()).unwrap()
+    );
+}
+
+#[test]
+fn quiet_flag_writes_only_the_final_state() {
+    let directory = TestDirectory::new("quiet");
+    let input = directory.write_program("program.json", &PROGRAM);
+    let succeeds = |output: &str, flags: &[&str]| {
+        run_simulator(&input, &directory.join(output), flags).status.success()
+    };
+    let read_log = |name: &str| -> Vec<Value> {
+        serde_json::from_str(&fs::read_to_string(directory.join(name)).unwrap()).unwrap()
+    };
+
+    assert!(succeeds("full.json", &[]));
+    for flag in ["--quiet", "--final-only"] {
+        assert!(succeeds("quiet.json", &[flag]));
+        let quiet_log = read_log("quiet.json");
+        assert_eq!(quiet_log.len(), 1);
+        assert_eq!(quiet_log.last(), read_log("full.json").last());
+    }
+    assert!(!succeeds("quiet.json", &["--quiet", "--trace"]));
+}
@@ -0,0 +1,50 @@
+use cpusim::architecture::ProcessorBuilder;
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+#[test]
+fn two_read_ports_dispatch_one_two_operand_instruction_per_cycle() {
+    let processor = ProcessorBuilder::new().read_ports(2).build().unwrap();
+    // Every operand is ready on dispatch, so each instruction reads two registers
+    let program = [
+        "add x1, x10, x11",
+        "sub x2, x12, x13",
+        "and x3, x14, x15",
+        "or x4, x16, x17",
+    ];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    // The four instructions are decoded together in cycle 1, then dispatched one per cycle
+    let dispatched: Vec<usize> = state_log[2..6]
+        .iter()
+        .map(|state| state.active_list().len() + state.committed_instructions() as usize)
+        .collect();
+    assert_eq!(dispatched, [1, 2, 3, 4]);
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.has_completed(&instructions));
+    assert_eq!(final_state.stall_report().read_ports, 3);
+}
+
+#[test]
+fn operands_waiting_for_a_result_use_no_read_port() {
+    let processor = ProcessorBuilder::new().read_ports(2).build().unwrap();
+    // The consumers only read x1 once it is forwarded, their immediates need no port either
+    let program = [
+        "add x1, x10, x11",
+        "addi x2, x1, 1",
+        "addi x3, x1, 2",
+        "addi x4, x1, 3",
+    ];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    assert_eq!(state_log[2].active_list().len(), 4);
+    assert_eq!(state_log.last().unwrap().stall_report().read_ports, 0);
+}
+
+#[test]
+fn register_file_needs_two_read_ports() {
+    assert!(ProcessorBuilder::new().read_ports(1).build().is_err());
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use std::collections::HashSet;
+
+use cpusim::architecture::ProcessorConfig;
+use cpusim::generator::generate_program;
+use cpusim::{parse_program, run};
+
+#[test]
+fn free_list_holds_exactly_the_unmapped_registers_after_completion() {
+    // Exercises retirement, an exception rollback, and a taken branch squash
+    let program = [
+        "addi x1, x0, 4",
+        "addi x2, x1, 4",
+        "beq x1, x1, 4",
+        "addi x3, x0, 1",
+        "mulu x3, x1, x2",
+        "add x3, x3, x3",
+        "divu x4, x3, x0",
+        "addi x5, x4, 1",
+    ];
+    let state_log = common::run(&program, ProcessorConfig::default());
+
+    for state in &state_log {
+        assert_eq!(state.check_register_accounting(), Ok(()));
+    }
+
+    let final_state = state_log.last().unwrap();
+    let free: HashSet<u8> = final_state.free_list().iter().copied().collect();
+    let mapped: HashSet<u8> = final_state.register_map_table().iter().copied().collect();
+    let unmapped: HashSet<u8> = (0..ProcessorConfig::default().physical_register_file_size)
+        .map(|register| register as u8)
+        .filter(|register| !mapped.contains(register))
+        .collect();
+    assert_eq!(free.len(), final_state.free_list().len());
+    assert_eq!(free, unmapped);
+}
+
+#[test]
+fn renaming_never_allocates_a_register_in_use() {
+    // Debug builds assert on every allocation that the register is unused, so the runs must
+    // finish without panicking while registers are recycled through a small free list
+    let config = ProcessorConfig {
+        physical_register_file_size: 40,
+        ..ProcessorConfig::default()
+    };
+    for seed in 0..20 {
+        let instructions = parse_program(&generate_program(60, seed)).unwrap();
+        let state_log = run(&instructions, config.clone(), 1000).unwrap();
+        assert!(state_log.last().unwrap().has_completed(&instructions));
+    }
+}
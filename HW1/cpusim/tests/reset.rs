@@ -0,0 +1,34 @@
+use cpusim::architecture::Processor;
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+#[test]
+fn reset_processor_runs_a_program_like_a_fresh_one() {
+    // The first program leaves registers, memory, counters and an exception behind
+    let first = ["addi x1, x0, 7", "st x1, 8(x0)", "mulu x2, x1, x1", "divu x3, x2, x0"];
+    let second = ["addi x1, x0, 1", "ld x2, 8(x0)", "add x3, x1, x2", "nop"];
+    let first = parse_program(&first.map(String::from)).unwrap();
+    let second = parse_program(&second.map(String::from)).unwrap();
+
+    let mut processor = Processor::new();
+    while processor.step(&first).unwrap() {}
+    assert!(processor.committed_instructions() > 0);
+    processor.reset();
+    assert_eq!(processor.pc(), 0);
+    assert_eq!(processor.committed_instructions(), 0);
+    assert!(processor.exception_cause().is_none());
+
+    let reset_log = run_on(processor, &second, MAX_CYCLES).unwrap();
+    let fresh_log = run_on(Processor::new(), &second, MAX_CYCLES).unwrap();
+
+    assert_eq!(
+        serde_json::to_string(&reset_log).unwrap(),
+        serde_json::to_string(&fresh_log).unwrap()
+    );
+    let (reset_final, fresh_final) = (reset_log.last().unwrap(), fresh_log.last().unwrap());
+    assert_eq!(reset_final.committed_instructions(), fresh_final.committed_instructions());
+    assert_eq!(reset_final.stall_report().free_list, fresh_final.stall_report().free_list);
+    assert_eq!(
+        reset_final.instruction_mix().counts,
+        fresh_final.instruction_mix().counts
+    );
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+
+#[test]
+fn instructions_retire_in_program_order_whatever_their_completion_order() {
+    // The long divisions complete after the younger additions, which must wait to retire
+    let program = [
+        "addi x1, x0, 100",
+        "addi x2, x0, 7",
+        "divu x3, x1, x2",
+        "addi x4, x0, 1",
+        "mulu x5, x2, x2",
+        "addi x6, x0, 2",
+        "remu x7, x1, x2",
+        "add x8, x4, x6",
+    ];
+    let config = ProcessorConfig {
+        mulu_latency: 3,
+        division_latency: 6,
+        ..ProcessorConfig::default()
+    };
+    let state_log = run(&program, config);
+
+    let retired: Vec<u64> = state_log
+        .iter()
+        .flat_map(|state| state.retired_instructions().to_vec())
+        .collect();
+    assert_eq!(retired, (0..program.len() as u64).collect::<Vec<u64>>());
+
+    // The younger additions were done while the division at PC 2 was still executing
+    assert!(state_log.iter().any(|state| {
+        let active_list = state.active_list();
+        active_list.iter().any(|entry| entry.pc == 2 && !entry.is_done)
+            && active_list.iter().any(|entry| entry.pc == 3 && entry.is_done)
+    }));
+}
@@ -0,0 +1,82 @@
+use cpusim::architecture::{Processor, ProcessorBuilder};
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+// The division traps while the younger instructions, renaming the same logical registers
+// several times, are still in the active list
+const PROGRAM: [&str; 12] = [
+    "addi x1, x0, 1",
+    "addi x2, x0, 2",
+    "divu x3, x1, x0",
+    "addi x1, x1, 1",
+    "addi x2, x2, 1",
+    "addi x1, x1, 1",
+    "addi x4, x2, 1",
+    "addi x2, x4, 1",
+    "addi x1, x2, 1",
+    "addi x5, x1, 1",
+    "addi x4, x5, 1",
+    "addi x6, x4, 1",
+];
+
+fn run_with_rollback(instant_rollback: bool) -> Vec<Processor> {
+    let processor = ProcessorBuilder::new()
+        .instant_rollback(instant_rollback)
+        .build()
+        .unwrap();
+    let instructions = parse_program(&PROGRAM.map(String::from)).unwrap();
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+    assert!(state_log.last().unwrap().has_completed(&instructions));
+    state_log
+}
+
+fn rolled_back_pcs(state_log: &[Processor]) -> Vec<u64> {
+    state_log
+        .iter()
+        .flat_map(|state| state.rolled_back_instructions().iter().copied())
+        .collect()
+}
+
+#[test]
+fn staged_rollback_undoes_commit_width_instructions_youngest_first() {
+    let state_log = run_with_rollback(false);
+
+    assert!(state_log.iter().any(|state| state.rolled_back_instructions().len() == 4));
+    assert!(state_log.iter().all(|state| state.rolled_back_instructions().len() <= 4));
+    let rolled_back = rolled_back_pcs(&state_log);
+    assert_eq!(rolled_back.first(), Some(&(PROGRAM.len() as u64 - 1)));
+    assert_eq!(rolled_back.last(), Some(&2));
+    assert!(rolled_back.windows(2).all(|pcs| pcs[0] > pcs[1]));
+}
+
+#[test]
+fn instant_rollback_undoes_the_active_list_in_one_cycle() {
+    let state_log = run_with_rollback(true);
+
+    let rollback_cycles: Vec<&Processor> = state_log
+        .iter()
+        .filter(|state| !state.rolled_back_instructions().is_empty())
+        .collect();
+    assert_eq!(rollback_cycles.len(), 1);
+    assert!(rollback_cycles[0].active_list().is_empty());
+    let rolled_back = rolled_back_pcs(&state_log);
+    assert_eq!(rolled_back.last(), Some(&2));
+    assert!(rolled_back.windows(2).all(|pcs| pcs[0] > pcs[1]));
+}
+
+#[test]
+fn both_rollbacks_recover_the_same_state() {
+    let staged = run_with_rollback(false);
+    let instant = run_with_rollback(true);
+
+    let staged_state = staged.last().unwrap();
+    let instant_state = instant.last().unwrap();
+    assert_eq!(staged_state.register_map_table(), instant_state.register_map_table());
+    assert_eq!(staged_state.free_list(), instant_state.free_list());
+    assert_eq!(staged_state.busy_bit_table(), instant_state.busy_bit_table());
+    assert_eq!(
+        staged_state.physical_register_file(),
+        instant_state.physical_register_file()
+    );
+    assert_eq!(staged_state.exception_pc(), instant_state.exception_pc());
+    assert!(instant.len() < staged.len());
+}
@@ -0,0 +1,21 @@
+use cpusim::architecture::ProcessorConfig;
+use cpusim::run_program;
+
+#[test]
+fn run_program_completes_and_commits_every_instruction() {
+    let program = [
+        "addi x1, x0, 1",
+        "addi x2, x0, 2",
+        "add x3, x1, x2",
+        "mulu x4, x3, x3",
+    ];
+    let instructions = program.iter().map(|x| x.to_string()).collect();
+
+    let state_log = run_program(instructions, ProcessorConfig::default()).unwrap();
+
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.is_done());
+    assert_eq!(final_state.committed_instructions(), 4);
+    let x4 = final_state.register_map_table()[4] as usize;
+    assert_eq!(final_state.physical_register_file()[x4], 9);
+}
@@ -0,0 +1,30 @@
+use cpusim::architecture::ProcessorConfig;
+use cpusim::{parse_program, run_programs, MAX_CYCLES};
+
+#[test]
+fn programs_run_back_to_back_on_fresh_processors() {
+    let first = ["addi x1, x0, 5", "addi x2, x1, 5"];
+    let second = ["addi x3, x0, 1", "mulu x4, x3, x3", "nop"];
+    let programs = vec![
+        ("first".to_string(), parse_program(&first.map(String::from)).unwrap()),
+        ("second".to_string(), parse_program(&second.map(String::from)).unwrap()),
+    ];
+
+    let logs = run_programs(&programs, ProcessorConfig::default(), MAX_CYCLES).unwrap();
+
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs[0].0, "first");
+    assert_eq!(logs[1].0, "second");
+    for ((_, instructions), (_, state_log)) in programs.iter().zip(&logs) {
+        let final_state = state_log.last().unwrap();
+        assert!(final_state.has_completed(instructions));
+        assert_eq!(final_state.committed_instructions(), instructions.len() as u64);
+        // Every program starts from the initial state
+        assert_eq!(state_log[0].pc(), 0);
+        assert!(state_log[0].active_list().is_empty());
+    }
+    // The second program does not see the registers written by the first
+    let second_final = logs[1].1.last().unwrap();
+    let x1 = second_final.register_map_table()[1] as usize;
+    assert_eq!(second_final.physical_register_file()[x1], 0);
+}
@@ -0,0 +1,44 @@
+use cpusim::architecture::ProcessorBuilder;
+use cpusim::{parse_program, run_on, run_on_until_idle, MAX_CYCLES};
+
+#[test]
+fn logging_stops_at_the_first_idle_cycle_before_the_exception_handler() {
+    // The division by zero rolls the prologue back, fetch then continues from the handler at PC 3
+    let program = [
+        "addi x1, x0, 1",
+        "divu x2, x1, x0",
+        "addi x3, x0, 3",
+        "addi x4, x0, 4",
+        "addi x5, x0, 5",
+    ];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+    let processor = ProcessorBuilder::new().exception_handler_pc(3).build().unwrap();
+
+    let prologue_log = run_on_until_idle(processor.clone(), &instructions, MAX_CYCLES).unwrap();
+    let full_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    let last_state = prologue_log.last().unwrap();
+    assert!(last_state.is_fully_idle());
+    assert!(!last_state.has_completed(&instructions));
+    assert!(prologue_log[1..prologue_log.len() - 1]
+        .iter()
+        .all(|state| !state.is_fully_idle()));
+    // Up to the idle cycle, the log is the one of the full run, which goes on with the handler
+    let first_idle = full_log.iter().skip(1).position(|state| state.is_fully_idle()).unwrap() + 1;
+    assert_eq!(prologue_log.len(), first_idle + 1);
+    assert!(full_log.len() > prologue_log.len());
+    assert!(full_log.last().unwrap().has_completed(&instructions));
+}
+
+#[test]
+fn program_without_idle_cycle_runs_to_completion() {
+    let program = ["addi x1, x0, 1", "mulu x2, x1, x1", "addi x3, x2, 1"];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+    let processor = ProcessorBuilder::new().build().unwrap();
+
+    let until_idle = run_on_until_idle(processor.clone(), &instructions, MAX_CYCLES).unwrap();
+    let full_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    assert_eq!(until_idle.len(), full_log.len());
+    assert!(until_idle.last().unwrap().has_completed(&instructions));
+}
@@ -0,0 +1,31 @@
+use cpusim::architecture::ProcessorBuilder;
+use cpusim::parse_program;
+
+#[test]
+fn instruction_woken_by_a_forwarded_result_issues_in_the_same_cycle() {
+    // The consumer waits in the integer queue until the multiplication result is forwarded
+    let program = ["addi x1, x0, 7", "mulu x2, x1, x1", "addi x3, x2, 1"];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+    let mut processor = ProcessorBuilder::new().mulu_latency(3).build().unwrap();
+
+    let mut wake_cycle = None;
+    let mut issue_cycle = None;
+    let mut cycle = 0;
+    while processor.step(&instructions).unwrap() {
+        cycle += 1;
+        let woken = processor
+            .forwarding_events()
+            .iter()
+            .any(|event| event.producer_pc == 1 && event.consumer_pc == 2);
+        if woken {
+            wake_cycle = Some(cycle);
+        }
+        if processor.issued_instructions().iter().any(|&(pc, _)| pc == 2) {
+            issue_cycle = Some(cycle);
+        }
+    }
+
+    assert!(wake_cycle.is_some());
+    assert_eq!(issue_cycle, wake_cycle);
+    assert_eq!(processor.architectural_registers()[3], 50);
+}
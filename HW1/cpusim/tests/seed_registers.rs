@@ -0,0 +1,69 @@
+mod common;
+
+use std::fs;
+
+use common::{run_simulator, TestDirectory};
+use cpusim::architecture::{Processor, ProcessorBuilder};
+use cpusim::{parse_program, parse_register_seed, run_on, MAX_CYCLES};
+use serde_json::Value;
+
+#[test]
+fn seeded_input_registers_are_read_by_the_program() {
+    let mut processor = Processor::new();
+    processor.seed_register(10, 5).unwrap();
+    processor.seed_register(11, 7).unwrap();
+    let instructions = parse_program(&["add x12, x10, x11".to_string()]).unwrap();
+
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    let final_state = state_log.last().unwrap();
+    assert!(final_state.has_completed(&instructions));
+    assert_eq!(final_state.architectural_registers()[10..13], [5, 7, 12]);
+    assert_eq!(state_log[0].free_list(), Processor::new().free_list());
+}
+
+#[test]
+fn register_seeds_parse_register_names_and_immediates() {
+    assert_eq!(parse_register_seed("x10=5"), Ok((10, 5)));
+    assert_eq!(parse_register_seed("a1=0x10"), Ok((11, 16)));
+    assert_eq!(parse_register_seed("x3=-1"), Ok((3, u64::MAX)));
+    assert!(parse_register_seed("x10").is_err());
+    assert!(parse_register_seed("x32=1").is_err());
+    assert!(parse_register_seed("x1=five").is_err());
+}
+
+#[test]
+fn registers_are_only_seeded_while_idle() {
+    let mut processor = Processor::new();
+    let instructions = parse_program(&["addi x1, x0, 1".to_string()]).unwrap();
+    processor.step(&instructions).unwrap();
+
+    assert!(processor.seed_register(1, 1).is_err());
+    assert!(Processor::new().seed_register(32, 1).is_err());
+}
+
+#[test]
+fn hardwired_x0_cannot_be_seeded() {
+    let mut processor = ProcessorBuilder::new()
+        .hardwired_zero_register(true)
+        .build()
+        .unwrap();
+
+    assert!(processor.seed_register(0, 1).is_err());
+    assert_eq!(processor.seed_register(0, 0), Ok(()));
+}
+
+#[test]
+fn register_flags_seed_the_initial_state() {
+    let directory = TestDirectory::new("seed");
+    let input = directory.write_program("sum.json", &["add x12, x10, x11"]);
+    let output = directory.join("sum_out.json");
+
+    let status = run_simulator(&input, &output, &["--reg", "x10=5", "--reg", "a1=7"]).status;
+
+    assert!(status.success());
+    let log: Vec<Value> = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+    let final_state = log.last().unwrap();
+    let physical_register = final_state["RegisterMapTable"][12].as_u64().unwrap() as usize;
+    assert_eq!(final_state["PhysicalRegisterFile"][physical_register], 12);
+}
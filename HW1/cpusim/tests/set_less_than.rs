@@ -0,0 +1,62 @@
+mod common;
+
+use common::run;
+use cpusim::arch_modules::Instruction;
+use cpusim::architecture::ProcessorConfig;
+
+fn final_registers(program: &[&str]) -> Vec<u64> {
+    let state_log = run(program, ProcessorConfig::default());
+    state_log.last().unwrap().architectural_registers()
+}
+
+#[test]
+fn signed_and_unsigned_comparisons_disagree_on_negative_values() {
+    let registers = final_registers(&[
+        "addi x1, x0, -1",
+        "addi x2, x0, 1",
+        "slt x3, x1, x2",
+        "sltu x4, x1, x2",
+        "slt x5, x2, x1",
+        "sltu x6, x2, x1",
+    ]);
+
+    // -1 is below 1 when signed, but reads as u64::MAX when unsigned
+    assert_eq!(registers[3..7], [1, 0, 0, 1]);
+}
+
+#[test]
+fn equal_operands_are_not_less_than() {
+    let registers = final_registers(&["addi x1, x0, 7", "slt x2, x1, x1", "sltu x3, x1, x1"]);
+
+    assert_eq!(registers[2..4], [0, 0]);
+}
+
+#[test]
+fn immediate_comparisons_use_the_sign_extended_immediate() {
+    let registers = final_registers(&[
+        "addi x1, x0, -1",
+        "addi x2, x0, 1",
+        "slti x3, x1, 0",
+        "sltiu x4, x1, 5",
+        "sltiu x5, x2, -1",
+        "slti x6, x2, -1",
+    ]);
+
+    assert_eq!(registers[3..7], [1, 0, 1, 0]);
+}
+
+#[test]
+fn comparisons_decode_and_format_back() {
+    let decoded = Instruction::new("sltiu x4, x1, 5".to_string()).decode(0).unwrap();
+    assert_eq!(decoded.op_code, "sltu");
+    assert!(decoded.immediate);
+    assert_eq!(decoded.to_string(), "sltiu x4, x1, 5");
+
+    let decoded = Instruction::new("slti x4, x1, -5".to_string()).decode(0).unwrap();
+    assert_eq!(decoded.op_code, "slt");
+    assert_eq!(decoded.to_string(), "slti x4, x1, -5");
+
+    let decoded = Instruction::new("SLT x4, x1, x2".to_string()).decode(0).unwrap();
+    assert_eq!(decoded.to_string(), "slt x4, x1, x2");
+    assert!(Instruction::new("sltui x4, x1, 5".to_string()).decode(0).is_err());
+}
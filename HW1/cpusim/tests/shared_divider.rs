@@ -0,0 +1,74 @@
+use cpusim::architecture::ProcessorBuilder;
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+/// Runs the program and returns every issue as (cycle, PC, ALU index).
+fn issues(program: &[&str], builder: ProcessorBuilder) -> Vec<(usize, u64, usize)> {
+    let instructions =
+        parse_program(&program.iter().map(|x| x.to_string()).collect::<Vec<_>>()).unwrap();
+    let state_log = run_on(builder.build().unwrap(), &instructions, MAX_CYCLES).unwrap();
+    assert!(state_log.last().unwrap().has_completed(&instructions));
+    state_log
+        .iter()
+        .enumerate()
+        .flat_map(|(cycle, state)| {
+            state
+                .issued_instructions()
+                .iter()
+                .map(move |&(pc, alu_index)| (cycle, pc, alu_index))
+        })
+        .collect()
+}
+
+const DIVISIONS: [&str; 4] = ["addi x1, x0, 12", "addi x2, x0, 4", "divu x3, x1, x2", "rem x4, x1, x2"];
+
+#[test]
+fn divisions_serialize_on_the_only_divider() {
+    let builder = ProcessorBuilder::new().division_latency(3).division_alus(&[2]);
+
+    let issues = issues(&DIVISIONS, builder);
+
+    let divisions: Vec<&(usize, u64, usize)> = issues.iter().filter(|(_, pc, _)| *pc >= 2).collect();
+    assert_eq!(divisions.len(), 2);
+    assert!(divisions.iter().all(|(_, _, alu_index)| *alu_index == 2));
+    // Both divisions are ready together, the second waits for the divider to be free again
+    let (first_cycle, second_cycle) = (divisions[0].0, divisions[1].0);
+    assert_eq!(second_cycle - first_cycle, 3);
+}
+
+#[test]
+fn divisions_issue_together_when_every_alu_divides() {
+    let builder = ProcessorBuilder::new().division_latency(3);
+
+    let issues = issues(&DIVISIONS, builder);
+
+    let divisions: Vec<&(usize, u64, usize)> = issues.iter().filter(|(_, pc, _)| *pc >= 2).collect();
+    assert_eq!(divisions[0].0, divisions[1].0);
+    assert_eq!((divisions[0].2, divisions[1].2), (0, 1));
+}
+
+#[test]
+fn other_instructions_issue_around_a_busy_divider() {
+    let program = [
+        "addi x1, x0, 12",
+        "addi x2, x0, 4",
+        "divu x3, x1, x2",
+        "divu x4, x2, x1",
+        "add x5, x1, x2",
+    ];
+    let builder = ProcessorBuilder::new().division_latency(3).division_alus(&[0]);
+
+    let issues = issues(&program, builder);
+
+    // The add, younger than the waiting division, goes to another ALU in the meantime
+    let issue_of = |pc: u64| *issues.iter().find(|(_, issued_pc, _)| *issued_pc == pc).unwrap();
+    assert_eq!(issue_of(2).2, 0);
+    assert_eq!(issue_of(3).2, 0);
+    assert!(issue_of(4).0 < issue_of(3).0);
+    assert_ne!(issue_of(4).2, 0);
+}
+
+#[test]
+fn divider_indices_must_name_existing_alus() {
+    assert!(ProcessorBuilder::new().division_alus(&[4]).build().is_err());
+    assert!(ProcessorBuilder::new().division_alus(&[]).build().is_err());
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+
+#[test]
+fn shifts_of_a_value_with_the_high_bit_set() {
+    let state_log = run(
+        &[
+            "addi x1, x0, -16",
+            "addi x2, x0, 4",
+            "addi x3, x0, 1",
+            "addi x4, x0, 68",
+            "sra x5, x1, x2",
+            "srl x6, x1, x2",
+            "sll x7, x3, x2",
+            "sll x8, x3, x4",
+            "srai x9, x1, 63",
+            "srli x10, x1, 63",
+            "slli x11, x3, 63",
+            "srai x12, x3, 1",
+        ],
+        ProcessorConfig::default(),
+    );
+    let registers = state_log.last().unwrap().architectural_registers();
+
+    // Arithmetic shifts copy the sign bit in, logical shifts fill with zeros
+    assert_eq!(registers[5..7], [u64::MAX, u64::MAX >> 4]);
+    // Only the low 6 bits of the shift amount are used, so shifting by 68 shifts by 4
+    assert_eq!(registers[7..9], [16, 16]);
+    assert_eq!(registers[9..13], [u64::MAX, 1, 1 << 63, 0]);
+}
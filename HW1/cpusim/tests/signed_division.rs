@@ -0,0 +1,59 @@
+mod common;
+
+use cpusim::architecture::{Processor, ProcessorConfig};
+
+fn run(program: &[&str]) -> Vec<Processor> {
+    common::run(program, ProcessorConfig::default())
+}
+
+#[test]
+fn negative_dividend_commits_signed_quotient_and_remainder() {
+    let program = [
+        "addi x1, x0, -7",
+        "addi x2, x0, 2",
+        "div x3, x1, x2",
+        "rem x4, x1, x2",
+        "divu x5, x1, x2",
+    ];
+
+    let state_log = run(&program);
+
+    let final_state = state_log.last().unwrap();
+    assert_eq!(final_state.committed_instructions(), 5);
+    let registers = final_state.architectural_registers();
+    // The quotient rounds toward zero and the remainder takes the sign of the dividend
+    assert_eq!(registers[3] as i64, -3);
+    assert_eq!(registers[4] as i64, -1);
+    // The same bits divided as unsigned values give a different quotient
+    assert_eq!(registers[5], (-7i64 as u64) / 2);
+}
+
+#[test]
+fn negative_divisor_commits_signed_quotient_and_remainder() {
+    let program = ["addi x1, x0, 7", "addi x2, x0, -2", "div x3, x1, x2", "rem x4, x1, x2"];
+
+    let registers = run(&program).last().unwrap().architectural_registers();
+
+    assert_eq!(registers[3] as i64, -3);
+    assert_eq!(registers[4] as i64, 1);
+}
+
+#[test]
+fn overflowing_signed_division_does_not_commit() {
+    let program = [
+        "addi x1, x0, 1",
+        "slli x1, x1, 63",
+        "addi x2, x0, -1",
+        "div x3, x1, x2",
+        "addi x4, x0, 1",
+    ];
+
+    let state_log = run(&program);
+
+    let exception_state = state_log.iter().find(|state| state.is_exception_mode()).unwrap();
+    assert_eq!(exception_state.exception_pc(), 3);
+    let final_state = state_log.last().unwrap();
+    // Only the three instructions older than the division retired
+    assert_eq!(final_state.committed_instructions(), 3);
+    assert_eq!(final_state.architectural_registers()[3..5], [0, 0]);
+}
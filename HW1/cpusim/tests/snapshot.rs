@@ -0,0 +1,50 @@
+use cpusim::architecture::Processor;
+use cpusim::export::VerboseState;
+use cpusim::parse_program;
+use serde_json::Value;
+
+/// Serializes every logged field along with the instructions in flight.
+fn full_state(processor: &Processor) -> Value {
+    serde_json::to_value(VerboseState::new(processor)).unwrap()
+}
+
+#[test]
+fn restore_returns_to_the_snapshot_state() {
+    let program = [
+        "addi x1, x0, 3",
+        "addi x2, x0, 4",
+        "mulu x3, x1, x2",
+        "add x4, x3, x1",
+        "sub x5, x4, x2",
+        "add x6, x5, x5",
+    ];
+    let instructions = parse_program(&program.map(String::from)).unwrap();
+    let mut processor = Processor::new();
+    for _ in 0..3 {
+        processor.step(&instructions).unwrap();
+    }
+
+    let snapshot = processor.snapshot();
+    let (expected_state, expected_pc, expected_committed) = (
+        full_state(&processor),
+        processor.pc(),
+        processor.committed_instructions(),
+    );
+    for _ in 0..4 {
+        processor.step(&instructions).unwrap();
+    }
+    assert_ne!(full_state(&processor), expected_state);
+
+    processor.restore(snapshot.clone());
+    assert_eq!(full_state(&processor), expected_state);
+    assert_eq!(processor.pc(), expected_pc);
+    assert_eq!(processor.committed_instructions(), expected_committed);
+
+    // The restored processor runs on exactly as the checkpointed one would
+    let mut replayed = Processor::new();
+    replayed.restore(snapshot);
+    while processor.step(&instructions).unwrap() {
+        replayed.step(&instructions).unwrap();
+        assert_eq!(full_state(&processor), full_state(&replayed));
+    }
+}
@@ -0,0 +1,127 @@
+use cpusim::architecture::{Processor, ProcessorBuilder};
+use cpusim::{parse_program, run_on, MAX_CYCLES};
+
+/// Returns the PCs in flight in the decoded buffer, the integer queue and the ALUs, each sorted.
+fn in_flight_pcs(processor: &Processor, program_len: u64) -> (Vec<u64>, Vec<u64>, Vec<u64>) {
+    let decoded = processor
+        .decoded_instructions()
+        .iter()
+        .map(|instruction| instruction.pc)
+        .collect();
+    let mut queued: Vec<u64> = processor.integer_queue().iter().map(|entry| entry.pc).collect();
+    queued.sort();
+    let executing = (0..program_len)
+        .filter(|&pc| processor.alus().iter().any(|alu| alu.holds(pc)))
+        .collect();
+    (decoded, queued, executing)
+}
+
+#[test]
+fn squash_removes_only_the_younger_instructions() {
+    // A long division chain keeps the ALUs, the integer queue and the decoded buffer occupied
+    let program: Vec<String> = [
+        "addi x1, x0, 100",
+        "addi x2, x0, 3",
+        "divu x3, x1, x2",
+        "divu x4, x1, x2",
+        "divu x5, x3, x2",
+        "divu x6, x4, x2",
+        "add x7, x5, x6",
+        "add x8, x7, x1",
+        "add x9, x8, x2",
+        "add x10, x9, x3",
+        "add x11, x10, x4",
+        "add x12, x11, x5",
+        "add x13, x12, x6",
+        "add x14, x13, x7",
+    ]
+    .map(String::from)
+    .to_vec();
+    let instructions = parse_program(&program).unwrap();
+    let mut processor = ProcessorBuilder::new().division_latency(4).build().unwrap();
+    let program_len = program.len() as u64;
+    let is_packed = |processor: &Processor| {
+        let (decoded, queued, executing) = in_flight_pcs(processor, program_len);
+        !decoded.is_empty() && !queued.is_empty() && !executing.is_empty()
+    };
+    while !is_packed(&processor) {
+        assert!(processor.step(&instructions).unwrap(), "the machine never filled up");
+    }
+    let (decoded, queued, executing) = in_flight_pcs(&processor, program_len);
+    let active_list = processor.active_list().to_vec();
+    let all_pcs: Vec<u64> = decoded.iter().chain(&queued).chain(&executing).copied().collect();
+    let squash_pc = (all_pcs.iter().min().unwrap() + all_pcs.iter().max().unwrap()) / 2;
+
+    processor.squash_younger_than(squash_pc);
+
+    let older = |pcs: &[u64]| -> Vec<u64> {
+        pcs.iter().copied().filter(|&pc| pc <= squash_pc).collect()
+    };
+    let (decoded_left, queued_left, executing_left) = in_flight_pcs(&processor, program_len);
+    assert_eq!(decoded_left, older(&decoded));
+    assert_eq!(queued_left, older(&queued));
+    assert_eq!(executing_left, older(&executing));
+    assert!(all_pcs.iter().any(|&pc| pc > squash_pc));
+    // The active list is left to the rollback
+    assert_eq!(processor.active_list().len(), active_list.len());
+}
+
+#[test]
+fn squash_keeps_the_older_half_of_a_fused_division() {
+    let program = [
+        "addi x1, x0, 100",
+        "addi x2, x0, 7",
+        "divu x3, x1, x2",
+        "remu x4, x1, x2",
+    ]
+    .map(String::from);
+    let instructions = parse_program(&program).unwrap();
+    let mut processor = ProcessorBuilder::new()
+        .fuse_divmod(true)
+        .division_latency(4)
+        .build()
+        .unwrap();
+    while !processor.alus().iter().any(|alu| alu.holds(3)) {
+        assert!(processor.step(&instructions).unwrap(), "the fused pair was never issued");
+    }
+
+    processor.squash_younger_than(2);
+
+    assert!(processor.alus().iter().any(|alu| alu.holds(2)));
+    assert!(!processor.alus().iter().any(|alu| alu.holds(3)));
+}
+
+#[test]
+fn exception_leaves_no_younger_instruction_in_flight() {
+    let program = [
+        "addi x1, x0, 5",
+        "addi x2, x0, 6",
+        "divu x3, x1, x0",
+        "addi x4, x1, 1",
+        "mulu x5, x4, x2",
+        "addi x6, x5, 1",
+        "addi x7, x6, 1",
+        "addi x8, x7, 1",
+    ]
+    .map(String::from);
+    let instructions = parse_program(&program).unwrap();
+    let processor = ProcessorBuilder::new().mulu_latency(3).build().unwrap();
+
+    let state_log = run_on(processor, &instructions, MAX_CYCLES).unwrap();
+
+    let exception_cycle = state_log
+        .iter()
+        .position(|state| state.is_exception_mode())
+        .unwrap();
+    let previous_state = &state_log[exception_cycle - 1];
+    assert!(!previous_state.integer_queue().is_empty());
+    let exception_state = &state_log[exception_cycle];
+    assert_eq!(exception_state.exception_pc(), 2);
+    assert!(exception_state.decoded_instructions().is_empty());
+    assert!(exception_state.integer_queue().is_empty());
+    assert!(exception_state.alus().iter().all(|alu| alu.is_idle()));
+    // The instructions older than the division retired, and their results survive the rollback
+    let final_registers = state_log.last().unwrap().architectural_registers();
+    assert_eq!(final_registers[1..3], [5, 6]);
+    assert_eq!(final_registers[4], 0);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{run_simulator, run_simulator_with_stdin, TestDirectory};
+use serde_json::Value;
+
+const PROGRAM: [&str; 3] = ["addi x1, x0, 3", "mulu x2, x1, x1", "sub x3, x2, x1"];
+
+#[test]
+fn program_is_read_from_stdin_when_the_input_is_a_dash() {
+    let directory = TestDirectory::new("stdio-stdin");
+    let input = directory.write_program("program.json", &PROGRAM);
+    let file_output = directory.join("file.json");
+    let stdin_output = directory.join("stdin.json");
+    assert!(run_simulator(&input, &file_output, &[]).status.success());
+
+    let program = serde_json::to_string(&PROGRAM).unwrap();
+    let run = run_simulator_with_stdin(&["-", stdin_output.to_str().unwrap()], &program);
+
+    assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+    assert_eq!(
+        fs::read_to_string(stdin_output).unwrap(),
+        fs::read_to_string(file_output).unwrap()
+    );
+}
+
+#[test]
+fn malformed_program_on_stdin_is_rejected() {
+    let directory = TestDirectory::new("stdio-malformed");
+    let output = directory.join("output.json");
+
+    let run = run_simulator_with_stdin(&["-", output.to_str().unwrap()], "[\"addi x1, x0, 3\"");
+
+    assert!(!run.status.success());
+    assert!(!output.exists());
+}
+
+#[test]
+fn log_is_written_to_stdout_when_the_output_is_a_dash() {
+    let directory = TestDirectory::new("stdio-stdout");
+    let input = directory.write_program("program.json", &PROGRAM);
+    let file_output = directory.join("file.json");
+    assert!(run_simulator(&input, &file_output, &[]).status.success());
+
+    let run = run_simulator(&input, Path::new("-"), &[]);
+
+    assert!(run.status.success());
+    let stdout = String::from_utf8(run.stdout).unwrap();
+    // The log is followed by a newline, unlike the file
+    assert_eq!(stdout, fs::read_to_string(file_output).unwrap() + "\n");
+    assert!(!Path::new("-").exists());
+}
+
+#[test]
+fn stdin_and_stdout_can_be_piped_together() {
+    let program = serde_json::to_string(&PROGRAM).unwrap();
+
+    let run = run_simulator_with_stdin(&["-", "-"], &program);
+
+    assert!(run.status.success());
+    let log: Value = serde_json::from_slice(&run.stdout).unwrap();
+    assert!(!log.as_array().unwrap().is_empty());
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::run;
+use cpusim::arch_modules::{ExceptionCause, Instruction};
+use cpusim::architecture::{Processor, ProcessorConfig};
+
+fn run_final(program: &[&str], trap_on_overflow: bool) -> Processor {
+    let config = ProcessorConfig {
+        trap_on_overflow,
+        ..ProcessorConfig::default()
+    };
+    let state_log = run(program, config);
+    state_log.last().unwrap().clone()
+}
+
+#[test]
+fn subi_subtracts_the_sign_extended_immediate() {
+    let processor = run_final(
+        &[
+            "addi x1, x0, 10",
+            "subi x2, x1, 3",
+            "subi x3, x1, 20",
+            "subi x4, x0, -4",
+            "subi x5, x2, 7",
+            "subi x1, x1, 1",
+        ],
+        false,
+    );
+    let registers = processor.architectural_registers();
+
+    assert_eq!(processor.exception_cause(), None);
+    // An immediate larger than the register value wraps around when not trapping
+    assert_eq!(registers[1..6], [9, 7, 10u64.wrapping_sub(20), 4, 0]);
+}
+
+#[test]
+fn subi_below_zero_traps_when_enabled() {
+    let processor = run_final(&["addi x1, x0, 10", "subi x2, x1, 3", "subi x3, x1, 20"], true);
+
+    assert_eq!(processor.exception_cause(), Some(ExceptionCause::Underflow));
+    assert_eq!(processor.exception_pc(), 2);
+    assert_eq!(processor.architectural_registers()[2], 7);
+}
+
+#[test]
+fn subi_decodes_to_an_immediate_sub() {
+    let decoded = Instruction::new("subi x2, x1, -4".to_string()).decode(0).unwrap();
+    assert_eq!(decoded.op_code, "sub");
+    assert!(decoded.immediate);
+    assert_eq!(decoded.to_string(), "subi x2, x1, -4");
+    assert!(Instruction::new("subi x2, x1".to_string()).decode(0).is_err());
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::{run_simulator, TestDirectory};
+
+const PROGRAM: [&str; 3] = ["addi x1, x0, 3", "mulu x2, x1, x1", "sub x3, x2, x1"];
+
+/// Returns the first cycle of the trace in which the stage lists the PC, e.g. "issue" listing
+/// "1->ALU0".
+fn stage_cycle(trace: &[String], stage: &str, pc: u64) -> Option<usize> {
+    trace.iter().position(|line| {
+        line.split(" | ")
+            .find_map(|field| field.strip_prefix(&format!("{}: ", stage)))
+            .is_some_and(|pcs| {
+                pcs.split(' ')
+                    .any(|entry| entry.split("->").next() == Some(&pc.to_string()))
+            })
+    })
+}
+
+#[test]
+fn trace_follows_every_instruction_through_the_pipeline() {
+    let directory = TestDirectory::new("trace");
+    let input = directory.write_program("program.json", &PROGRAM);
+
+    let run = run_simulator(&input, &directory.join("output.json"), &["--trace"]);
+
+    assert!(run.status.success());
+    let stderr = String::from_utf8(run.stderr).unwrap();
+    let trace: Vec<String> = stderr
+        .lines()
+        .filter(|line| line.starts_with("Cycle ") && line.contains("fetch/decode: "))
+        .map(|line| line.split_once(": ").unwrap().1.to_string())
+        .collect();
+    assert!(!trace.is_empty());
+    for pc in 0..PROGRAM.len() as u64 {
+        let fetch = stage_cycle(&trace, "fetch/decode", pc).unwrap();
+        let dispatch = stage_cycle(&trace, "dispatch", pc).unwrap();
+        let issue = stage_cycle(&trace, "issue", pc).unwrap();
+        let commit = stage_cycle(&trace, "commit", pc).unwrap();
+        assert!(fetch < dispatch && dispatch < issue && issue < commit, "PC {}", pc);
+    }
+}
+
+#[test]
+fn trace_lines_are_numbered_by_cycle() {
+    let directory = TestDirectory::new("trace-cycles");
+    let input = directory.write_program("program.json", &PROGRAM);
+
+    let run = run_simulator(&input, &directory.join("output.json"), &["--trace"]);
+
+    let stderr = String::from_utf8(run.stderr).unwrap();
+    let cycles: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.contains("fetch/decode: "))
+        .map(|line| line.split_once(':').unwrap().0)
+        .collect();
+    let expected: Vec<String> = (1..=cycles.len())
+        .map(|cycle| format!("Cycle {}", cycle))
+        .collect();
+    assert_eq!(cycles, expected);
+}
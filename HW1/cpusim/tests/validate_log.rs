@@ -0,0 +1,141 @@
+mod common;
+
+use std::fs;
+use std::process::Command;
+
+use common::{run, TestDirectory};
+use cpusim::architecture::ProcessorConfig;
+use cpusim::export::{log_to_json, validate_log};
+use serde_json::Value;
+
+/// Returns the JSON log of a program exercising renaming, an exception and its rollback.
+fn known_good_log() -> Value {
+    let program = [
+        "addi x1, x0, 4",
+        "mulu x2, x1, x1",
+        "add x3, x2, x1",
+        "divu x4, x3, x0",
+        "addi x5, x4, 1",
+        "addi x1, x1, 1",
+    ];
+    let state_log = run(&program, ProcessorConfig::default());
+    serde_json::from_str(&log_to_json(&state_log).unwrap()).unwrap()
+}
+
+#[test]
+fn known_good_log_is_consistent() {
+    let log = known_good_log();
+
+    assert_eq!(validate_log(&log.to_string(), &ProcessorConfig::default()), Ok(()));
+}
+
+#[test]
+fn leaked_register_is_reported() {
+    let mut log = known_good_log();
+    let free_list = log[2]["FreeList"].as_array_mut().unwrap();
+    let leaked = free_list.pop().unwrap();
+
+    assert_eq!(
+        validate_log(&log.to_string(), &ProcessorConfig::default()),
+        Err(format!(
+            "Cycle 2: Physical register {} is neither free, mapped, nor the old destination of \
+             an in-flight instruction",
+            leaked
+        ))
+    );
+}
+
+#[test]
+fn register_claimed_by_an_instruction_without_destination_is_reported() {
+    let config = ProcessorConfig {
+        hardwired_zero_register: true,
+        mulu_latency: 3,
+        ..ProcessorConfig::default()
+    };
+    let program = ["addi x1, x0, 4", "mulu x2, x1, x1", "nop", "addi x3, x2, 1"];
+    let state_log = run(&program, config.clone());
+    let mut log: Value = serde_json::from_str(&log_to_json(&state_log).unwrap()).unwrap();
+    assert_eq!(validate_log(&log.to_string(), &config), Ok(()));
+
+    // The nop frees nothing when it retires, so it cannot hold the leaked register
+    let cycle = state_log
+        .iter()
+        .position(|state| state.active_list().iter().any(|entry| entry.pc == 2))
+        .unwrap();
+    let leaked = log[cycle]["FreeList"].as_array_mut().unwrap().pop().unwrap();
+    let nop = log[cycle]["ActiveList"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|entry| entry["PC"] == 2)
+        .unwrap();
+    nop["OldDestination"] = leaked.clone();
+
+    assert_eq!(
+        validate_log(&log.to_string(), &config),
+        Err(format!(
+            "Cycle {}: Physical register {} is neither free, mapped, nor the old destination of \
+             an in-flight instruction",
+            cycle, leaked
+        ))
+    );
+}
+
+#[test]
+fn busy_free_register_is_reported() {
+    let mut log = known_good_log();
+    log[1]["BusyBitTable"][63] = Value::Bool(true);
+
+    assert_eq!(
+        validate_log(&log.to_string(), &ProcessorConfig::default()),
+        Err("Cycle 1: Physical register 63 is busy without being the destination of an \
+             in-flight instruction"
+            .to_string())
+    );
+}
+
+#[test]
+fn register_both_free_and_mapped_is_reported() {
+    let mut log = known_good_log();
+    log[0]["RegisterMapTable"][5] = Value::from(40);
+
+    assert_eq!(
+        validate_log(&log.to_string(), &ProcessorConfig::default()),
+        Err("Cycle 0: Physical register 40 is both free and mapped".to_string())
+    );
+}
+
+#[test]
+fn overfull_active_list_is_reported() {
+    let log = known_good_log();
+    let config = ProcessorConfig {
+        active_list_size: 2,
+        ..ProcessorConfig::default()
+    };
+
+    let error = validate_log(&log.to_string(), &config).unwrap_err();
+    assert!(error.contains("Active list holds"), "{}", error);
+}
+
+#[test]
+fn validate_log_flag_checks_a_saved_log() {
+    let directory = TestDirectory::new("validate");
+    let good = directory.join("good.json");
+    let corrupted = directory.join("corrupted.json");
+    let mut log = known_good_log();
+    fs::write(&good, log.to_string()).unwrap();
+    log[1]["BusyBitTable"][63] = Value::Bool(true);
+    fs::write(&corrupted, log.to_string()).unwrap();
+
+    let validate = |path| {
+        Command::new(env!("CARGO_BIN_EXE_cpusim"))
+            .arg("--validate-log")
+            .arg(path)
+            .status()
+            .unwrap()
+            .success()
+    };
+
+    assert!(validate(&good));
+    assert!(!validate(&corrupted));
+}
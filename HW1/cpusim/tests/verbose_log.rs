@@ -0,0 +1,30 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+use cpusim::export::verbose_log;
+use serde_json::Value;
+
+#[test]
+fn verbose_log_shows_the_instructions_in_flight() {
+    let program = ["addi x1, x0, 1", "addi x2, x1, 1", "addi x3, x2, 1", "addi x4, x3, 1"];
+    let state_log = run(&program, ProcessorConfig::default());
+
+    let log = serde_json::to_value(verbose_log(&state_log)).unwrap();
+    let cycles = log.as_array().unwrap();
+    assert_eq!(cycles.len(), state_log.len());
+
+    // Cycle 1 decodes the four instructions
+    let decoded = cycles[1]["DecodedInstructions"].as_array().unwrap();
+    assert_eq!(decoded.len(), 4);
+    assert_eq!(decoded[0]["OpCode"], "add");
+
+    // Cycle 3 issues the first addition, which then sits in the first stage of ALU 0
+    let alus = cycles[3]["ALUs"].as_array().unwrap();
+    assert_eq!(alus.len(), ProcessorConfig::default().alu_count);
+    assert_eq!(alus[0]["Stage1"]["PC"], 0);
+    assert!(alus[1]["Stage1"].is_null());
+
+    // The regular fields are still logged
+    assert!(matches!(cycles[3]["ActiveList"], Value::Array(_)));
+}
@@ -0,0 +1,17 @@
+mod common;
+
+use common::run;
+use cpusim::architecture::ProcessorConfig;
+use cpusim::export::{VersionedLog, LOG_FORMAT_VERSION};
+
+#[test]
+fn versioned_log_holds_the_version_and_every_cycle() {
+    let program = ["addi x1, x0, 1", "addi x2, x1, 1"];
+    let state_log = run(&program, ProcessorConfig::default());
+
+    let log = serde_json::to_value(VersionedLog::new(&state_log)).unwrap();
+
+    assert_eq!(log["version"], LOG_FORMAT_VERSION);
+    assert_eq!(log["cycles"].as_array().unwrap().len(), state_log.len());
+    assert_eq!(log.as_object().unwrap().len(), 2);
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use cpusim::arch_modules::ExceptionCause;
+use cpusim::architecture::{Processor, ProcessorConfig};
+
+fn run(program: &[&str], trap_on_overflow: bool) -> Processor {
+    let config = ProcessorConfig {
+        trap_on_overflow,
+        ..ProcessorConfig::default()
+    };
+    let state_log = common::run(program, config);
+    state_log.last().unwrap().clone()
+}
+
+const OVERFLOWING_ADD: [&str; 3] = ["addi x1, x0, 1", "slli x2, x1, 63", "add x3, x2, x2"];
+const UNDERFLOWING_SUB: [&str; 3] = ["addi x1, x0, 1", "addi x2, x0, 2", "sub x3, x1, x2"];
+
+#[test]
+fn overflowing_add_wraps_by_default() {
+    let processor = run(&OVERFLOWING_ADD, false);
+    assert_eq!(processor.exception_cause(), None);
+    assert_eq!(processor.architectural_registers()[3], 0);
+}
+
+#[test]
+fn overflowing_add_traps_when_enabled() {
+    let processor = run(&OVERFLOWING_ADD, true);
+    assert_eq!(processor.exception_cause(), Some(ExceptionCause::Overflow));
+    assert_eq!(processor.exception_pc(), 2);
+}
+
+#[test]
+fn underflowing_sub_wraps_by_default() {
+    let processor = run(&UNDERFLOWING_SUB, false);
+    assert_eq!(processor.exception_cause(), None);
+    assert_eq!(processor.architectural_registers()[3], u64::MAX);
+}
+
+#[test]
+fn underflowing_sub_traps_when_enabled() {
+    let processor = run(&UNDERFLOWING_SUB, true);
+    assert_eq!(processor.exception_cause(), Some(ExceptionCause::Underflow));
+    assert_eq!(processor.exception_pc(), 2);
+}
+
+#[test]
+fn division_by_zero_traps_in_both_modes() {
+    let program = ["addi x1, x0, 1", "remu x2, x1, x0"];
+    for trap_on_overflow in [false, true] {
+        let processor = run(&program, trap_on_overflow);
+        assert_eq!(processor.exception_cause(), Some(ExceptionCause::DivideByZero));
+    }
+}
@@ -1,8 +1,8 @@
 #!/bin/bash
 
-# Check if exactly two arguments are provided
-if [ "$#" -ne 2 ]; then
-    echo "Usage: $0 input_file.json output_file.json"
+# Check if two arguments (and an optional cycle cap) are provided
+if [ "$#" -ne 2 ] && [ "$#" -ne 3 ]; then
+    echo "Usage: $0 input_file.json output_file.json [max_cycles]"
     exit 1
 fi
 
@@ -10,5 +10,4 @@ fi
 input_file=$1
 output_file=$2
 
-cd cpusim/src &&
-cargo run -- "$input_file" "$output_file"
+cargo run --manifest-path cpusim/Cargo.toml -- "$input_file" "$output_file" $3